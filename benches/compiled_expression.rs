@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use expression_eval_rs::{compile, evaluate_expression_with_context};
+use expression_eval_rs::definition::Literal;
+
+const EXPRESSION: &str = "( price * quantity - discount ) / 2 > 100 && quantity < 50";
+
+fn evaluate_many_records(c: &mut Criterion) {
+    let mut group = c.benchmark_group("evaluate 1000 records");
+
+    group.bench_function("evaluate_expression_with_context", |b| {
+        b.iter(|| {
+            for record in 0..1000 {
+                let quantity = (record % 60).to_string();
+                let context = BTreeMap::from([("price", "12.5"), ("quantity", quantity.as_str()), ("discount", "3")]);
+                black_box(evaluate_expression_with_context(EXPRESSION, &context).unwrap());
+            }
+        })
+    });
+
+    let compiled = compile(EXPRESSION).unwrap();
+    group.bench_function("CompiledExpression::eval", |b| {
+        b.iter(|| {
+            for record in 0..1000 {
+                let context = BTreeMap::from([("price", Literal::Decimal(12.5)), ("quantity", Literal::Integer(record % 60)), ("discount", Literal::Integer(3))]);
+                black_box(compiled.eval(&context).unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, evaluate_many_records);
+criterion_main!(benches);
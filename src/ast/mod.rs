@@ -0,0 +1,86 @@
+//!
+//! Abstract syntax tree of a parsed expression.
+//!
+//! Every node remembers the [Span] of the source it was parsed from. The [Display] implementation
+//! prints the expression back in a normalized form, e.g. `(1+2)*a` becomes `(1 + 2) * a` and
+//! `a ? b : c` becomes `if a then b else c`.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use crate::definition::{Literal, Operator};
+use crate::error::Span;
+use crate::path::VariablePath;
+
+
+/// Deepest nesting of an expression, counted in nodes from the root to the innermost leaf. Deeper expressions are
+/// rejected with [crate::error::EvalError::TooDeep] while parsing and expanding derived variables, so walking
+/// the tree recursively can not overflow the stack.
+pub const MAX_DEPTH: usize = 256;
+
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    Literal { value: Literal, span: Span },
+    Variable { path: VariablePath, span: Span },
+    Unary { operator: Operator, operand: Box<Expr>, span: Span },
+    Binary { operator: Operator, left: Box<Expr>, right: Box<Expr>, span: Span },
+    Grouping { inner: Box<Expr>, span: Span },
+    Call { name: Box<str>, arguments: Vec<Expr>, span: Span },
+    /// `condition ? then_branch : else_branch` or `if condition then then_branch else else_branch`, only the chosen
+    /// branch is evaluated. A null condition chooses the else branch.
+    Conditional { condition: Box<Expr>, then_branch: Box<Expr>, else_branch: Box<Expr>, span: Span },
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Literal { span, .. }
+            | Expr::Variable { span, .. }
+            | Expr::Unary { span, .. }
+            | Expr::Binary { span, .. }
+            | Expr::Grouping { span, .. }
+            | Expr::Call { span, .. }
+            | Expr::Conditional { span, .. } => *span,
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Expr::Literal { value, .. } => {
+                write!(f, "{}", value)
+            }
+            Expr::Variable { path, .. } => {
+                write!(f, "{}", path)
+            }
+            Expr::Unary { operator: operator @ (Operator::IsNull | Operator::IsNotNull), operand, .. } => {
+                write!(f, "{} {}", operand, operator.symbol())
+            }
+            Expr::Unary { operator, operand, .. } => {
+                write!(f, "{}{}", operator.symbol(), operand)
+            }
+            Expr::Binary { operator, left, right, .. } => {
+                write!(f, "{} {} {}", left, operator.symbol(), right)
+            }
+            Expr::Grouping { inner, .. } => {
+                write!(f, "({})", inner)
+            }
+            Expr::Call { name, arguments, .. } => {
+                write!(f, "{}(", name)?;
+                for (index, argument) in arguments.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", argument)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Conditional { condition, then_branch, else_branch, .. } => {
+                // the keyword form reads the same when it is nested into another expression
+                write!(f, "if {} then {} else {}", condition, then_branch, else_branch)
+            }
+        }
+    }
+}
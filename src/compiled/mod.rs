@@ -0,0 +1,108 @@
+//!
+//! Expressions which are tokenized and parsed once and evaluated many times.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//! use expression_eval_rs::compile;
+//! use expression_eval_rs::definition::Literal;
+//!
+//! let expression = compile("price * 2 > 10").unwrap();
+//!
+//! for price in [3, 7] {
+//!     let context = BTreeMap::from([("price", Literal::Integer(price))]);
+//!     expression.eval(&context).unwrap();
+//! }
+//! ```
+
+use alloc::boxed::Box;
+use crate::ast::Expr;
+use crate::context::Context;
+use crate::error::EvalError;
+use crate::eval::evaluate_ast;
+use crate::function::FunctionRegistry;
+use crate::Literal;
+use crate::options::EvalOptions;
+
+
+#[derive(Debug, Clone)]
+pub struct CompiledExpression {
+    source: Box<str>,
+    expression: Expr,
+    options: EvalOptions,
+    functions: FunctionRegistry,
+}
+
+impl CompiledExpression {
+    pub(crate) fn new(source: &str, expression: Expr, options: EvalOptions, functions: FunctionRegistry) -> Self {
+        Self { source: Box::from(source), expression, options, functions }
+    }
+
+    /// Evaluates the expression, resolving its variables from the given context.
+    pub fn eval<C: Context + ?Sized>(&self, context: &C) -> Result<Literal, EvalError> {
+        evaluate_ast(&self.expression, context, &self.functions, &self.options)
+    }
+
+    /// The expression the handle was compiled from, e.g. to render a [crate::diagnostic::Diagnostic].
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn expression(&self) -> &Expr {
+        &self.expression
+    }
+
+    pub fn options(&self) -> &EvalOptions {
+        &self.options
+    }
+
+    pub fn functions(&self) -> &FunctionRegistry {
+        &self.functions
+    }
+}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::btree_map::BTreeMap;
+    use crate::{compile, compile_with_options};
+    use crate::context::EmptyContext;
+    use crate::error::{EvalError, Span};
+    use crate::options::{EvalOptions, LexMode};
+    use crate::Literal::{Boolean, Decimal, Integer};
+
+
+    #[test]
+    fn compile_once_evaluate_many() {
+        let expression = compile("price * quantity > 100").unwrap();
+
+        let cheap = BTreeMap::from([("price", Decimal(9.5)), ("quantity", Integer(10))]);
+        let expensive = BTreeMap::from([("price", Decimal(10.5)), ("quantity", Integer(10))]);
+
+        assert_eq!(expression.eval(&cheap), Ok(Boolean(false)));
+        assert_eq!(expression.eval(&expensive), Ok(Boolean(true)));
+    }
+
+    #[test]
+    fn compile_reports_parse_errors() {
+        assert_eq!(compile("(1 + 2").err(), Some(EvalError::UnbalancedParenthesis { span: Span::new(0, 1) }));
+    }
+
+    #[test]
+    fn eval_reports_missing_variables() {
+        let expression = compile("1 + missing").unwrap();
+
+        assert_eq!(expression.source(), "1 + missing");
+        assert_eq!(expression.eval(&EmptyContext), Err(EvalError::UnknownVariable { name: "missing".into(), span: Span::new(4, 11) }));
+    }
+
+    #[test]
+    fn compile_with_lenient_lexing() {
+        let options = EvalOptions::default().with_lex_mode(LexMode::Lenient);
+        let expression = compile_with_options("1 + 2 $", &options).unwrap();
+
+        assert_eq!(expression.options(), &options);
+        assert_eq!(expression.eval(&EmptyContext), Ok(Integer(3)));
+    }
+}
@@ -1,42 +1,144 @@
 extern crate alloc;
 
 use alloc::collections::btree_map::BTreeMap;
-use alloc::vec::Vec;
-use alloc::string::String;
-use crate::definition::Token;
-use crate::{Literal, string_to_tokens};
+use alloc::string::ToString;
+use core::borrow::Borrow;
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+use alloc::boxed::Box;
+use crate::ast::Expr;
+use crate::definition::Operator;
+use crate::path::{PathSegment, VariablePath};
+use crate::{Literal, parse};
 
 
+/// Resolves the values of the variables used in an expression while it is evaluated.
+///
+/// Values are only requested for the variables an expression actually reaches, so a context can look them up
+/// lazily from its own data. Besides maps and [EmptyContext], closures taking a [VariablePath] are contexts
+/// and structs can be exposed through [FieldContext].
+pub trait Context {
+    fn get(&self, path: &VariablePath) -> Option<Literal>;
+}
 
-pub fn replace_variables_with_values_from_context(tokens: Vec<Token>, context: &BTreeMap<&str, &str>) -> Result<Vec<Token>, &'static str> {
-    let x: Vec<Token> = tokens.into_iter()
-        .flat_map(|element| find_and_replace(element, context))
-        .collect();
+impl<K: Borrow<str> + Ord> Context for BTreeMap<K, Literal> {
+    fn get(&self, path: &VariablePath) -> Option<Literal> {
+        resolve_with(path, |name| BTreeMap::get(self, name).cloned())
+    }
+}
 
-    Ok(x)
+#[cfg(feature = "std")]
+impl<K: Borrow<str> + Eq + Hash, S: BuildHasher> Context for HashMap<K, Literal, S> {
+    fn get(&self, path: &VariablePath) -> Option<Literal> {
+        resolve_with(path, |name| HashMap::get(self, name).cloned())
+    }
 }
 
-fn find_and_replace(token: Token, context: &BTreeMap<&str, &str>) -> Vec<Token> {
-    match token {
-        Token::Variable(variable) => {
-            let option = context.get(&*variable).unwrap();
-            let result = string_to_tokens(&option).unwrap();
-            result.into_iter().map(|token| map_token_to(token)).collect()
-        }
-        _ => {
-            Vec::from([token])
-        }
+impl<F: Fn(&VariablePath) -> Option<Literal>> Context for F {
+    fn get(&self, path: &VariablePath) -> Option<Literal> {
+        self(path)
+    }
+}
+
+/// A context without any variables.
+pub struct EmptyContext;
+
+impl Context for EmptyContext {
+    fn get(&self, _path: &VariablePath) -> Option<Literal> {
+        None
+    }
+}
+
+
+/// Implemented by structs which expose their fields to expressions through a [FieldContext].
+pub trait Fields {
+    fn field(&self, name: &str) -> Option<Literal>;
+
+    /// A nested struct, used for paths like `user.address.zip`.
+    fn nested(&self, _name: &str) -> Option<&dyn Fields> {
+        None
     }
 }
 
-fn map_token_to(token: Token) -> Token {
-    match token {
-        Token::Variable(variable) => {
-            Token::Literal(Literal::String(variable))
+/// Adapts a struct implementing [Fields] to a [Context].
+///
+/// ```
+/// use expression_eval_rs::compile;
+/// use expression_eval_rs::context::{FieldContext, Fields};
+/// use expression_eval_rs::definition::Literal;
+///
+/// struct Order { total: i64 }
+///
+/// impl Fields for Order {
+///     fn field(&self, name: &str) -> Option<Literal> {
+///         match name {
+///             "total" => Some(Literal::Integer(self.total)),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let expression = compile("total > 100").unwrap();
+/// assert_eq!(expression.eval(&FieldContext(&Order { total: 120 })), Ok(Literal::Boolean(true)));
+/// ```
+pub struct FieldContext<'a, T: Fields>(pub &'a T);
+
+impl<T: Fields> Context for FieldContext<'_, T> {
+    fn get(&self, path: &VariablePath) -> Option<Literal> {
+        let mut fields: &dyn Fields = self.0;
+        let mut name = path.root();
+        let mut segments = path.segments();
+
+        while let [PathSegment::Field(next) | PathSegment::Key(next), remaining @ ..] = segments {
+            match fields.nested(name) {
+                Some(nested) => {
+                    fields = nested;
+                    name = next;
+                    segments = remaining;
+                }
+                None => break,
+            }
         }
-        _ => { token }
+        index_into(fields.field(name)?, segments)
+    }
+}
+
+
+/// Resolves a path with a lookup by name, as the map contexts do. The whole path is looked up first, so flat keys
+/// like `order.total` work, otherwise the indexes of the path are applied to the list stored under its root.
+pub fn resolve_with<F: Fn(&str) -> Option<Literal>>(path: &VariablePath, lookup: F) -> Option<Literal> {
+    match path.as_identifier() {
+        Some(name) => lookup(name),
+        None => lookup(&path.to_string()).or_else(|| index_into(lookup(path.root())?, path.segments())),
     }
+}
+
+fn index_into(value: Literal, segments: &[PathSegment]) -> Option<Literal> {
+    segments.iter().try_fold(value, |value, segment| match (value, segment) {
+        (Literal::List(mut values), PathSegment::Index(index)) if *index < values.len() => Some(values.swap_remove(*index)),
+        _ => None,
+    })
+}
+
 
+/// Reads a context value given as text. Single literals like `42`, `-4.5`, `true` or `'text'` keep their type,
+/// any other text is a plain string, so `"4.5 > 3"` is the string `'4.5 > 3'` and never part of the expression.
+/// Values which should be evaluated as expressions are defined as [crate::derived::DerivedVariables].
+pub fn parse_value(value: &str) -> Literal {
+    let literal = match parse(value) {
+        Ok(Expr::Literal { value, .. }) => Some(value),
+        Ok(Expr::Unary { operator: Operator::UnaryMinus, operand, .. }) => match *operand {
+            Expr::Literal { value: Literal::Integer(value), .. } => value.checked_neg().map(Literal::Integer),
+            Expr::Literal { value: Literal::Decimal(value), .. } => Some(Literal::Decimal(-value)),
+            #[cfg(feature = "decimal")]
+            Expr::Literal { value: Literal::Money(value), .. } => Some(Literal::Money(-value)),
+            _ => None,
+        },
+        _ => None,
+    };
+    literal.unwrap_or_else(|| Literal::String(Box::from(value)))
 }
 
 #[cfg(test)]
@@ -45,39 +147,129 @@ mod tests {
 
     use alloc::collections::btree_map::BTreeMap;
     use std::prelude::rust_2021::Vec;
-    use crate::context::replace_variables_with_values_from_context;
-    use crate::definition::Token::Literal;
-    use crate::string_to_tokens;
-    use crate::Literal::{Boolean, Decimal, Integer};
-    use crate::tokenizer::{EQUAL_OPERATOR, GREATER_OPERATOR};
+    use core::cell::Cell;
+    use std::string::String;
+    use crate::compile;
+    use rstest::rstest;
+    use crate::context::{Context, FieldContext, Fields, parse_value};
+    use crate::error::{EvalError, Span};
+    use crate::Literal;
+    use crate::path::VariablePath;
+    use crate::Literal::{Boolean, Decimal, Integer, List};
 
 
+    #[rstest]
+    #[case::integer("3", Integer(3))]
+    #[case::negative_decimal("-4.5", Decimal(-4.5))]
+    #[case::boolean("true", Boolean(true))]
+    #[case::quoted("'4.5'", Literal::String("4.5".into()))]
+    #[case::word("A", Literal::String("A".into()))]
+    #[case::expression("4.5 > 3", Literal::String("4.5 > 3".into()))]
+    #[case::negated_word("-a", Literal::String("-a".into()))]
+    #[case::invalid("1.2.3", Literal::String("1.2.3".into()))]
+    fn values_are_atomic(#[case] value: &str, #[case] expected: Literal) {
+        assert_eq!(parse_value(value), expected);
+    }
+
+    #[cfg(feature = "decimal")]
     #[test]
-    fn simple_replacements_of_variables() {
-        let mut some_map = BTreeMap::new();
-        some_map.insert("first", "4.5");
-        some_map.insert("second", "3");
-        some_map.insert("third", "true");
+    fn money_values() {
+        assert_eq!(parse_value("-19.99d"), Literal::Money(rust_decimal::Decimal::new(-1999, 2)));
+        assert_eq!(parse_value("19.99"), Decimal(19.99));
+    }
 
-        let tokens = string_to_tokens("first > second == third").unwrap();
-        let vec = replace_variables_with_values_from_context(tokens, &some_map).unwrap();
+    #[test]
+    fn resolve_paths() {
+        let items = List(Vec::from([Integer(10), List(Vec::from([Integer(20)]))]));
+        let context = BTreeMap::from([("order.total", Integer(7)), ("items", items)]);
+
+        assert_eq!(Context::get(&context, &VariablePath::new("order").field("total")), Some(Integer(7)));
+        assert_eq!(Context::get(&context, &VariablePath::new("items").index(1).index(0)), Some(Integer(20)));
+        assert_eq!(Context::get(&context, &VariablePath::new("items").index(2)), None);
+        assert_eq!(Context::get(&context, &VariablePath::new("items").field("length")), None);
+        assert_eq!(compile("items[0] + order.total").unwrap().eval(&context), Ok(Integer(17)));
+    }
+
+    #[test]
+    fn resolve_flat_keys_with_non_ascii_characters() {
+        let context = BTreeMap::from([("tags['größe']", Integer(42)), ("tags['it\\'s']", Integer(1))]);
+
+        assert_eq!(compile("tags['größe'] + tags['it\\'s']").unwrap().eval(&context), Ok(Integer(43)));
+    }
 
-        let expected = Vec::from([Literal(Decimal(4.5)), GREATER_OPERATOR, Literal(Integer(3)), EQUAL_OPERATOR, Literal(Boolean(true))]);
+    #[test]
+    fn maps_with_owned_keys() {
+        let context: BTreeMap<String, Literal> = BTreeMap::from([(String::from("price"), Integer(3))]);
 
-        assert_eq!(vec, expected);
+        assert_eq!(compile("price * 2").unwrap().eval(&context), Ok(Integer(6)));
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn complex_replacements_of_variables() {
-        let mut some_map = BTreeMap::new();
-        some_map.insert("first", "4.5 > 3");
-        some_map.insert("second", "true");
+    fn hash_maps() {
+        let context = std::collections::HashMap::from([("price", Integer(3)), ("order.total", Integer(4))]);
+
+        assert_eq!(compile("price * order.total").unwrap().eval(&context), Ok(Integer(12)));
+    }
 
-        let tokens = string_to_tokens("first == second").unwrap();
-        let vec = replace_variables_with_values_from_context(tokens, &some_map).unwrap();
+    #[test]
+    fn closures_resolve_lazily() {
+        let requested = Cell::new(0);
+        let context = |path: &VariablePath| {
+            requested.set(requested.get() + 1);
+            path.as_identifier().and_then(|name| name.strip_prefix("x")).and_then(|index| index.parse().ok()).map(Integer)
+        };
 
-        let expected = Vec::from([Literal(Decimal(4.5)), GREATER_OPERATOR, Literal(Integer(3)), EQUAL_OPERATOR, Literal(Boolean(true))]);
+        assert_eq!(compile("x1 + x20").unwrap().eval(&context), Ok(Integer(21)));
+        assert_eq!(compile("x1 > 5 && x2 > 5").unwrap().eval(&context), Ok(Boolean(false)));
+        assert_eq!(requested.get(), 3);
+    }
+
+    struct Address {
+        zip: &'static str,
+    }
 
-        assert_eq!(vec, expected);
+    struct User {
+        name: &'static str,
+        address: Address,
+        scores: Vec<i64>,
     }
-}
\ No newline at end of file
+
+    impl Fields for Address {
+        fn field(&self, name: &str) -> Option<Literal> {
+            match name {
+                "zip" => Some(Literal::String(self.zip.into())),
+                _ => None,
+            }
+        }
+    }
+
+    impl Fields for User {
+        fn field(&self, name: &str) -> Option<Literal> {
+            match name {
+                "name" => Some(Literal::String(self.name.into())),
+                "scores" => Some(List(self.scores.iter().copied().map(Integer).collect())),
+                _ => None,
+            }
+        }
+
+        fn nested(&self, name: &str) -> Option<&dyn Fields> {
+            match name {
+                "address" => Some(&self.address),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn struct_fields() {
+        let user = User { name: "Ada", address: Address { zip: "8010" }, scores: Vec::from([3, 9]) };
+        let context = FieldContext(&user);
+
+        assert_eq!(compile("user_name").unwrap().eval(&context), Err(EvalError::UnknownVariable { name: "user_name".into(), span: Span::new(0, 9) }));
+        assert_eq!(compile("name + ' ' + address.zip").unwrap().eval(&context), Ok(Literal::String("Ada 8010".into())));
+        assert_eq!(compile("scores[1] - scores[0]").unwrap().eval(&context), Ok(Integer(6)));
+        assert_eq!(Context::get(&context, &VariablePath::new("address").field("street")), None);
+        assert_eq!(Context::get(&context, &VariablePath::new("address")), None);
+    }
+}
@@ -1,13 +1,94 @@
 
 use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use crate::error::Span;
+use crate::path::VariablePath;
 
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Literal {
     String(Box<str>),
     Boolean(bool),
     Decimal(f64),
     Integer(i64),
+    /// The absence of a value, written `null`. Missing variables are null if [crate::options::MissingVariables::Null]
+    /// is set, how operators treat null operands is controlled by [crate::options::NullLogic].
+    Null,
+    /// Produced by functions like `split`, there is no literal syntax for lists.
+    List(Vec<Literal>),
+    /// An exact base 10 decimal written like `19.99d`, so `0.1d + 0.2d == 0.3d` holds. Integers are converted to
+    /// money exactly, mixing it with decimals is a type mismatch. See [crate::options::EvalOptions::money_scale].
+    #[cfg(feature = "decimal")]
+    Money(rust_decimal::Decimal),
+}
+
+impl Literal {
+    pub fn literal_type(&self) -> LiteralType {
+        match self {
+            Literal::String(_) => LiteralType::String,
+            Literal::Boolean(_) => LiteralType::Boolean,
+            Literal::Decimal(_) => LiteralType::Decimal,
+            Literal::Integer(_) => LiteralType::Integer,
+            Literal::Null => LiteralType::Null,
+            Literal::List(_) => LiteralType::List,
+            #[cfg(feature = "decimal")]
+            Literal::Money(_) => LiteralType::Money,
+        }
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Literal::String(value) => write!(f, "'{}'", value),
+            Literal::Boolean(value) => write!(f, "{}", value),
+            // debug formatting keeps the fraction of whole decimals, e.g. 3.0
+            Literal::Decimal(value) => write!(f, "{:?}", value),
+            Literal::Integer(value) => write!(f, "{}", value),
+            Literal::Null => write!(f, "null"),
+            Literal::List(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            #[cfg(feature = "decimal")]
+            Literal::Money(value) => write!(f, "{}d", value),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum LiteralType {
+    String,
+    Boolean,
+    Decimal,
+    Integer,
+    Null,
+    List,
+    #[cfg(feature = "decimal")]
+    Money,
+}
+
+impl Display for LiteralType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            LiteralType::String => "string",
+            LiteralType::Boolean => "boolean",
+            LiteralType::Decimal => "decimal",
+            LiteralType::Integer => "integer",
+            LiteralType::Null => "null",
+            LiteralType::List => "list",
+            #[cfg(feature = "decimal")]
+            LiteralType::Money => "money",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 
@@ -15,8 +96,34 @@ pub enum Literal {
 pub enum Token {
     Operator(OperatorProperties),
     Literal(Literal),
-    Variable(Box<str>),
+    Variable(VariablePath),
     Parenthesis(Parenthesis),
+    Comma,
+    /// `?` of the `condition ? a : b` conditional, `??` is the coalesce operator.
+    QuestionMark,
+    Colon,
+    Keyword(Keyword),
+}
+
+/// A [Token] together with the position it was read from.
+#[derive(Debug, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+impl SpannedToken {
+    pub fn new(token: Token, span: Span) -> Self {
+        Self { token, span }
+    }
+}
+
+/// Keywords of the `if condition then a else b` conditional.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Keyword {
+    If,
+    Then,
+    Else,
 }
 
 #[derive(Debug, PartialEq)]
@@ -25,20 +132,174 @@ pub enum Parenthesis {
     RightParenthesis,
 }
 
+/// How an operator is parsed, one row of [OPERATORS].
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct OperatorProperties {
+    /// Operators with a higher precedence bind tighter, all precedences are above the one of the conditional `a ? b : c`.
     pub precedence: i8,
     pub symbol: &'static str,
-    pub left_associative: bool,
+    pub associativity: Associativity,
+    pub fixity: Fixity,
     pub operator: Operator,
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+impl OperatorProperties {
+    const fn new(operator: Operator, symbol: &'static str, precedence: i8, fixity: Fixity, associativity: Associativity) -> Self {
+        Self { precedence, symbol, associativity, fixity, operator }
+    }
+
+    pub const fn arity(&self) -> Arity {
+        self.fixity.arity()
+    }
+}
+
+/// How a chain of operators with the same precedence groups, `a - b - c` is `(a - b) - c` while `a ^ b ^ c` is
+/// `a ^ (b ^ c)`. Prefix operators always group from the right and postfix operators from the left.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// Position of an operator relative to its operands.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Fixity {
+    /// In front of its single operand, like `-a`.
+    Prefix,
+    /// Between its two operands, like `a + b`.
+    Infix,
+    /// After its single operand, like `a is null`.
+    Postfix,
+}
+
+impl Fixity {
+    pub const fn arity(self) -> Arity {
+        match self {
+            Fixity::Prefix | Fixity::Postfix => Arity::Unary,
+            Fixity::Infix => Arity::Binary,
+        }
+    }
+}
+
+/// Number of operands an operator takes.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Arity {
+    Unary,
+    Binary,
+}
+
+/// Every operator ordered from the weakest to the tightest binding, this table drives the tokenizer and the parser.
+///
+/// Operators of the same precedence must share their fixity and associativity, otherwise a chain of them would be
+/// ambiguous. This is checked when the crate is compiled.
+pub const OPERATORS: [OperatorProperties; OPERATOR_COUNT] = [
+    OperatorProperties::new(Operator::Or, "||", 1, Fixity::Infix, Associativity::Left),
+
+    OperatorProperties::new(Operator::And, "&&", 2, Fixity::Infix, Associativity::Left),
+
+    OperatorProperties::new(Operator::NotEqual, "!=", 3, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::Equal, "==", 3, Fixity::Infix, Associativity::Left),
+
+    // between equality and ordering, so a == b is null is a == (b is null) and a < b is null is (a < b) is null
+    OperatorProperties::new(Operator::IsNull, "is null", 4, Fixity::Postfix, Associativity::Left),
+    OperatorProperties::new(Operator::IsNotNull, "is not null", 4, Fixity::Postfix, Associativity::Left),
+
+    OperatorProperties::new(Operator::Greater, ">", 5, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::GreaterOrEqual, ">=", 5, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::Less, "<", 5, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::LessOrEqual, "<=", 5, Fixity::Infix, Associativity::Left),
+
+    // bitwise operators bind tighter than comparisons, so a & 1 == 1 is (a & 1) == 1
+    OperatorProperties::new(Operator::BitOr, "|", 6, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::BitXor, "xor", 7, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::BitAnd, "&", 8, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::ShiftLeft, "<<", 9, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::ShiftRight, ">>", 9, Fixity::Infix, Associativity::Left),
+
+    // binds tighter than comparisons but looser than arithmetic, so a > b ?? 1 + 2 is a > (b ?? (1 + 2)),
+    // and groups from the right, so every variable of a ?? b ?? c may be missing
+    OperatorProperties::new(Operator::Coalesce, "??", 10, Fixity::Infix, Associativity::Right),
+
+    OperatorProperties::new(Operator::Plus, "+", 11, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::Minus, "-", 11, Fixity::Infix, Associativity::Left),
+
+    OperatorProperties::new(Operator::Divide, "/", 12, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::IntegerDivide, "//", 12, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::Multiply, "*", 12, Fixity::Infix, Associativity::Left),
+    OperatorProperties::new(Operator::Modulo, "%", 12, Fixity::Infix, Associativity::Left),
+
+    OperatorProperties::new(Operator::Not, "!", 13, Fixity::Prefix, Associativity::Right),
+    OperatorProperties::new(Operator::BitNot, "~", 13, Fixity::Prefix, Associativity::Right),
+    OperatorProperties::new(Operator::UnaryMinus, "-", 13, Fixity::Prefix, Associativity::Right),
+    OperatorProperties::new(Operator::UnaryPlus, "+", 13, Fixity::Prefix, Associativity::Right),
+
+    // binds tighter than the prefix operators, so -2 ^ 2 is -(2 ^ 2)
+    OperatorProperties::new(Operator::PowerOf, "^", 14, Fixity::Infix, Associativity::Right),
+];
+
+const _: () = assert!(is_valid_operator_table(&OPERATORS), "invalid operator table");
+
+/// Number of [Operator] variants. The match stops compiling when a variant is added, which is the reminder to count it.
+const OPERATOR_COUNT: usize = match Operator::Or {
+    Operator::Or | Operator::And | Operator::Not
+    | Operator::UnaryMinus | Operator::UnaryPlus
+    | Operator::NotEqual | Operator::Equal
+    | Operator::Greater | Operator::GreaterOrEqual | Operator::Less | Operator::LessOrEqual
+    | Operator::Plus | Operator::Minus | Operator::Divide | Operator::Multiply | Operator::Modulo | Operator::IntegerDivide
+    | Operator::BitAnd | Operator::BitOr | Operator::BitXor | Operator::BitNot | Operator::ShiftLeft | Operator::ShiftRight
+    | Operator::PowerOf
+    | Operator::Coalesce
+    | Operator::IsNull | Operator::IsNotNull => 27,
+};
+
+/// Every operator has exactly one row, as there is a row per operator and no operator has two. The rows are ordered by
+/// precedence and rows with the same precedence agree on fixity and associativity. Prefix operators group from the
+/// right and postfix operators from the left.
+const fn is_valid_operator_table(table: &[OperatorProperties]) -> bool {
+    if table.len() != OPERATOR_COUNT {
+        return false;
+    }
+    let mut index = 0;
+    while index < table.len() {
+        let row = &table[index];
+        if row.precedence <= 0 {
+            return false;
+        }
+        match (row.fixity, row.associativity) {
+            (Fixity::Prefix, Associativity::Left) | (Fixity::Postfix, Associativity::Right) => return false,
+            _ => {}
+        }
+        let mut other = 0;
+        while other < table.len() {
+            if other != index && table[other].operator as u8 == row.operator as u8 {
+                return false;
+            }
+            other += 1;
+        }
+        if index > 0 {
+            let previous = &table[index - 1];
+            if previous.precedence > row.precedence {
+                return false;
+            }
+            if previous.precedence == row.precedence
+                && (previous.fixity as u8 != row.fixity as u8 || previous.associativity as u8 != row.associativity as u8) {
+                return false;
+            }
+        }
+        index += 1;
+    }
+    true
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Operator {
     Or,
     And,
     Not,
 
+    UnaryMinus,
+    UnaryPlus,
+
     NotEqual,
     Equal,
 
@@ -51,7 +312,35 @@ pub enum Operator {
     Minus,
     Divide,
     Multiply,
+    Modulo,
+    IntegerDivide,
+
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
 
     PowerOf,
 
-}
\ No newline at end of file
+    Coalesce,
+
+    IsNull,
+    IsNotNull,
+}
+
+impl Operator {
+    /// The row of this operator in [OPERATORS].
+    pub const fn properties(self) -> OperatorProperties {
+        let mut index = 0;
+        while OPERATORS[index].operator as u8 != self as u8 {
+            index += 1;
+        }
+        OPERATORS[index]
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        self.properties().symbol
+    }
+}
@@ -0,0 +1,310 @@
+//!
+//! Derived variables, named sub-expressions which are expanded into the expressions using them.
+//!
+//! Context values are always plain data, a context value `"4.5 > 3"` is the string `'4.5 > 3'`. Variables which
+//! really are expressions have to be defined explicitly, their definitions are parsed once and spliced into every
+//! expression referring to them, as if the definition had been written there in parenthesis. Definitions are lexed
+//! with the [LexMode] of the expression using them, a definition with unknown characters can only be used by
+//! expressions compiled with [LexMode::Lenient].
+//!
+//! ```
+//! use expression_eval_rs::compile_with_derived;
+//! use expression_eval_rs::derived::DerivedVariables;
+//! use expression_eval_rs::definition::Literal;
+//! use expression_eval_rs::function::FunctionRegistry;
+//! use expression_eval_rs::options::EvalOptions;
+//! use std::collections::BTreeMap;
+//!
+//! let mut derived = DerivedVariables::new();
+//! derived
+//!     .define("total", "price * quantity").unwrap()
+//!     .define("is_large_order", "total > 100").unwrap();
+//!
+//! let expression = compile_with_derived("is_large_order && total < 1000", &derived, &EvalOptions::default(), &FunctionRegistry::with_builtins()).unwrap();
+//! let context = BTreeMap::from([("price", Literal::Integer(20)), ("quantity", Literal::Integer(6))]);
+//! assert_eq!(expression.eval(&context), Ok(Literal::Boolean(true)));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::btree_map::BTreeMap;
+use alloc::vec::Vec;
+use crate::ast::{Expr, MAX_DEPTH};
+use crate::error::{EvalError, ParseError, Span};
+use crate::options::LexMode;
+use crate::parser::parse_tokens;
+use crate::tokenizer::{string_to_tokens, string_to_tokens_with_mode};
+
+
+/// Most nodes an expression may have after its derived variables are expanded. Every reference copies the whole
+/// definition, so a few definitions referring to each other can otherwise describe an expression of any size.
+pub const MAX_EXPANDED_NODES: usize = 1 << 18;
+
+#[derive(Debug, Clone, Default)]
+pub struct DerivedVariables {
+    definitions: BTreeMap<Box<str>, Definition>,
+}
+
+/// A definition lexed with [LexMode::Lenient], which accepts everything [LexMode::Strict] accepts and lexes it the
+/// same, together with the error a strict lexer reports for it.
+#[derive(Debug, Clone)]
+struct Definition {
+    expression: Expr,
+    strict_error: Option<EvalError>,
+}
+
+impl DerivedVariables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines or replaces a derived variable. Definitions may refer to derived variables which are defined later,
+    /// a definition which makes a variable depend on itself is rejected with [EvalError::CyclicDefinition],
+    /// located at the reference in the given definition. Unknown characters are reported when an expression compiled
+    /// with [LexMode::Strict] uses the definition.
+    pub fn define(&mut self, name: &str, definition: &str) -> Result<&mut Self, ParseError> {
+        let expression = string_to_tokens_with_mode(definition, LexMode::Lenient)
+            .and_then(parse_tokens)?;
+        self.check_cycles(name, &expression)?;
+        let strict_error = string_to_tokens(definition).err();
+        self.definitions.insert(Box::from(name), Definition { expression, strict_error });
+        Ok(self)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Expr> {
+        self.definitions.get(name).map(|definition| &definition.expression)
+    }
+
+    /// Replaces every derived variable in the expression by its definition. The spliced nodes are located at the
+    /// variable they replace, so errors inside of a definition point to the place it is used at. Fails with
+    /// [EvalError::TooDeep] if the expanded expression is nested deeper than [MAX_DEPTH] and with
+    /// [EvalError::TooLarge] if it has more than [MAX_EXPANDED_NODES] nodes, e.g. because every definition refers to
+    /// the previous one twice. With [LexMode::Strict] a definition with unknown characters fails with the
+    /// [EvalError::Lex] error, located at the variable.
+    pub fn expand(&self, expression: Expr, lex_mode: LexMode) -> Result<Expr, ParseError> {
+        let mut budget = MAX_EXPANDED_NODES;
+        self.expand_at(&expression, lex_mode, 1, None, &mut budget)
+    }
+
+    /// Copies the expression with its derived variables expanded. Inside of a definition `location` is the span of
+    /// the outermost variable it replaces, every node takes that span.
+    fn expand_at(&self, expression: &Expr, lex_mode: LexMode, depth: usize, location: Option<Span>, budget: &mut usize) -> Result<Expr, ParseError> {
+        let span = location.unwrap_or(expression.span());
+        if depth > MAX_DEPTH {
+            return Err(EvalError::TooDeep { span });
+        }
+        if *budget == 0 {
+            return Err(EvalError::TooLarge { span });
+        }
+        *budget -= 1;
+
+        Ok(match expression {
+            Expr::Variable { path, .. } => match path.as_identifier().and_then(|name| self.definitions.get(name)) {
+                Some(Definition { strict_error: Some(error), .. }) if lex_mode == LexMode::Strict => {
+                    return Err(error.clone().with_span(span));
+                }
+                Some(definition) => {
+                    let inner = self.expand_at(&definition.expression, lex_mode, depth + 1, Some(span), budget)?;
+                    Expr::Grouping { inner: Box::new(inner), span }
+                }
+                None => Expr::Variable { path: path.clone(), span },
+            },
+            Expr::Literal { value, .. } => Expr::Literal { value: value.clone(), span },
+            Expr::Unary { operator, operand, .. } => {
+                Expr::Unary { operator: *operator, operand: Box::new(self.expand_at(operand, lex_mode, depth + 1, location, budget)?), span }
+            }
+            Expr::Binary { operator, left, right, .. } => {
+                let left = self.expand_at(left, lex_mode, depth + 1, location, budget)?;
+                let right = self.expand_at(right, lex_mode, depth + 1, location, budget)?;
+                Expr::Binary { operator: *operator, left: Box::new(left), right: Box::new(right), span }
+            }
+            Expr::Grouping { inner, .. } => {
+                Expr::Grouping { inner: Box::new(self.expand_at(inner, lex_mode, depth + 1, location, budget)?), span }
+            }
+            Expr::Call { name, arguments, .. } => {
+                let arguments = arguments.iter()
+                    .map(|argument| self.expand_at(argument, lex_mode, depth + 1, location, budget))
+                    .collect::<Result<Vec<Expr>, ParseError>>()?;
+                Expr::Call { name: name.clone(), arguments, span }
+            }
+            Expr::Conditional { condition, then_branch, else_branch, .. } => Expr::Conditional {
+                condition: Box::new(self.expand_at(condition, lex_mode, depth + 1, location, budget)?),
+                then_branch: Box::new(self.expand_at(then_branch, lex_mode, depth + 1, location, budget)?),
+                else_branch: Box::new(self.expand_at(else_branch, lex_mode, depth + 1, location, budget)?),
+                span,
+            },
+        })
+    }
+
+    /// Follows the derived variables the new definition refers to and fails if one of them leads back to `name`.
+    fn check_cycles(&self, name: &str, expression: &Expr) -> Result<(), EvalError> {
+        let mut pending = Vec::new();
+        references(expression, &mut pending);
+        let mut visited: Vec<&str> = Vec::new();
+
+        for (reference, span) in pending {
+            let mut stack = Vec::from([reference]);
+            while let Some(current) = stack.pop() {
+                if current == name {
+                    return Err(EvalError::CyclicDefinition { name: Box::from(name), span });
+                }
+                if visited.contains(&current) {
+                    continue;
+                }
+                visited.push(current);
+                if let Some(definition) = self.get(current) {
+                    let mut nested = Vec::new();
+                    references(definition, &mut nested);
+                    stack.extend(nested.into_iter().map(|(nested, _)| nested));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Collects the plain identifiers the expression refers to, together with their position.
+fn references<'a>(expression: &'a Expr, found: &mut Vec<(&'a str, Span)>) {
+    match expression {
+        Expr::Variable { path, span } => {
+            if let Some(name) = path.as_identifier() {
+                found.push((name, *span));
+            }
+        }
+        Expr::Literal { .. } => {}
+        Expr::Unary { operand, .. } => references(operand, found),
+        Expr::Binary { left, right, .. } => {
+            references(left, found);
+            references(right, found);
+        }
+        Expr::Grouping { inner, .. } => references(inner, found),
+        Expr::Call { arguments, .. } => arguments.iter().for_each(|argument| references(argument, found)),
+        Expr::Conditional { condition, then_branch, else_branch, .. } => {
+            references(condition, found);
+            references(then_branch, found);
+            references(else_branch, found);
+        }
+    }
+}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+    use std::string::ToString;
+    use alloc::collections::btree_map::BTreeMap;
+    use crate::{compile_with_derived, parse};
+    use crate::context::EmptyContext;
+    use crate::definition::{LiteralType, Operator};
+    use crate::derived::DerivedVariables;
+    use crate::error::{EvalError, Span};
+    use crate::function::{ArgumentCount, FunctionRegistry};
+    use crate::Literal::{Boolean, Integer};
+    use crate::options::{EvalOptions, LexMode, MissingVariables};
+
+
+    fn derived() -> DerivedVariables {
+        let mut derived = DerivedVariables::new();
+        derived
+            .define("total", "price * quantity").unwrap()
+            .define("discounted", "total - discount").unwrap()
+            .define("discount", "2").unwrap();
+        derived
+    }
+
+    #[test]
+    fn expand_keeps_precedence() {
+        let expression = derived().expand(parse("discounted * 2").unwrap(), LexMode::Strict).unwrap();
+
+        assert_eq!(expression.to_string(), "((price * quantity) - (2)) * 2");
+    }
+
+    #[test]
+    fn evaluate_derived_variables() {
+        let expression = compile_with_derived("discounted > 10", &derived(), &EvalOptions::default(), &FunctionRegistry::with_builtins()).unwrap();
+
+        assert_eq!(expression.eval(&BTreeMap::from([("price", Integer(4)), ("quantity", Integer(3))])), Ok(Boolean(false)));
+        assert_eq!(expression.eval(&BTreeMap::from([("price", Integer(4)), ("quantity", Integer(4))])), Ok(Boolean(true)));
+    }
+
+    #[test]
+    fn errors_point_to_the_derived_variable() {
+        let expression = compile_with_derived("1 + total", &derived(), &EvalOptions::default(), &FunctionRegistry::with_builtins()).unwrap();
+
+        assert_eq!(expression.eval(&BTreeMap::from([("price", Integer(4))])), Err(EvalError::UnknownVariable { name: "quantity".into(), span: Span::new(4, 9) }));
+        assert_eq!(expression.eval(&BTreeMap::from([("price", Boolean(true)), ("quantity", Integer(4))])), Err(EvalError::TypeMismatch { operator: Operator::Multiply, left: LiteralType::Boolean, right: LiteralType::Integer, span: Span::new(4, 9) }));
+    }
+
+    #[test]
+    fn compile_with_options_and_functions() {
+        let mut derived = derived();
+        derived.define("answered", "answer() + discount").unwrap();
+        let mut functions = FunctionRegistry::new();
+        functions.register("answer", ArgumentCount::exactly(0), |_| Ok(Integer(42)));
+        let options = EvalOptions::default().with_missing_variables(MissingVariables::Null);
+
+        let expression = compile_with_derived("answered + (quantity ?? 1)", &derived, &options, &functions).unwrap();
+        assert_eq!(expression.eval(&EmptyContext), Ok(Integer(45)));
+
+        derived.define("length", "len('a')").unwrap();
+        assert_eq!(compile_with_derived("1 + length", &derived, &options, &functions).err(), Some(EvalError::UnknownFunction { name: "len".into(), span: Span::new(4, 10) }));
+    }
+
+    #[test]
+    fn lex_definitions_like_the_expression() {
+        let mut derived = derived();
+        derived.define("skipped", "discount $ + 1").unwrap();
+        let lenient = EvalOptions::default().with_lex_mode(LexMode::Lenient);
+
+        let expression = compile_with_derived("skipped * 2", &derived, &lenient, &FunctionRegistry::with_builtins()).unwrap();
+        assert_eq!(expression.eval(&EmptyContext), Ok(Integer(6)));
+        assert_matches!(compile_with_derived("1 + skipped", &derived, &EvalOptions::default(), &FunctionRegistry::with_builtins()), Err(EvalError::Lex { span, .. }) => assert_eq!(span, Span::new(4, 11)));
+        assert!(compile_with_derived("discounted", &derived, &EvalOptions::default(), &FunctionRegistry::with_builtins()).is_ok());
+    }
+
+    #[test]
+    fn reject_cycles() {
+        let mut derived = derived();
+
+        assert_eq!(derived.define("price", "discounted / 2").err(), Some(EvalError::CyclicDefinition { name: "price".into(), span: Span::new(0, 10) }));
+        assert_eq!(derived.define("a", "1 + a").err(), Some(EvalError::CyclicDefinition { name: "a".into(), span: Span::new(4, 5) }));
+        assert!(derived.get("price").is_none());
+
+        derived.define("b", "c + 1").unwrap();
+        assert_eq!(derived.define("c", "b * 2").err(), Some(EvalError::CyclicDefinition { name: "c".into(), span: Span::new(0, 1) }));
+    }
+
+    #[test]
+    fn replace_definitions() {
+        let mut derived = derived();
+        derived.define("discount", "quantity").unwrap();
+
+        assert_eq!(derived.expand(parse("discounted").unwrap(), LexMode::Strict).unwrap().to_string(), "((price * quantity) - (quantity))");
+    }
+
+    #[test]
+    fn reject_expansions_nested_too_deeply() {
+        let mut derived = DerivedVariables::new();
+        derived
+            .define("inner", &format!("{}1{}", "(".repeat(150), ")".repeat(150))).unwrap()
+            .define("outer", &format!("{}inner{}", "(".repeat(150), ")".repeat(150))).unwrap();
+
+        assert!(derived.expand(parse("inner + 1").unwrap(), LexMode::Strict).is_ok());
+        assert_eq!(derived.expand(parse("1 + outer").unwrap(), LexMode::Strict).err(), Some(EvalError::TooDeep { span: Span::new(4, 9) }));
+        assert_eq!(compile_with_derived("outer", &derived, &EvalOptions::default(), &FunctionRegistry::with_builtins()).err(), Some(EvalError::TooDeep { span: Span::new(0, 5) }));
+    }
+
+    #[test]
+    fn reject_expansions_growing_too_large() {
+        let mut derived = DerivedVariables::new();
+        derived.define("v0", "x").unwrap();
+        for level in 1..=40 {
+            derived.define(&format!("v{level}"), &format!("v{} + v{}", level - 1, level - 1)).unwrap();
+        }
+
+        let expression = compile_with_derived("v10", &derived, &EvalOptions::default(), &FunctionRegistry::with_builtins()).unwrap();
+        assert_eq!(expression.eval(&BTreeMap::from([("x", Integer(1))])), Ok(Integer(1024)));
+        assert_eq!(compile_with_derived("1 + v40", &derived, &EvalOptions::default(), &FunctionRegistry::with_builtins()).err(), Some(EvalError::TooLarge { span: Span::new(4, 7) }));
+    }
+}
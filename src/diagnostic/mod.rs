@@ -0,0 +1,170 @@
+//!
+//! Renders an [EvalError] as a human readable report pointing at the offending part of the expression.
+//!
+//! ```text
+//! error: division by zero
+//!   --> 1:5
+//!   |
+//! 1 | 1 + 4 / 0
+//!   |     ^^^^^ division by zero
+//!   |
+//!   = hint: make sure the divisor can never be zero
+//! ```
+
+use alloc::string::{String, ToString};
+use core::cmp::{max, min};
+use core::fmt::{Display, Formatter};
+use crate::error::{EvalError, LexErrorKind};
+
+
+pub struct Diagnostic<'a> {
+    source: &'a str,
+    error: &'a EvalError,
+}
+
+impl<'a> Diagnostic<'a> {
+    pub fn new(source: &'a str, error: &'a EvalError) -> Self {
+        Self { source, error }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self.error {
+            EvalError::Lex { .. } => "invalid token",
+            EvalError::UnbalancedParenthesis { .. } => "unbalanced parenthesis",
+            EvalError::TypeMismatch { .. } | EvalError::UnsupportedOperand { .. } => "type mismatch",
+            EvalError::ArityMismatch { .. } => "wrong number of operands",
+            EvalError::UnknownVariable { .. } => "unknown variable",
+            EvalError::DivisionByZero { .. } => "division by zero",
+            EvalError::Overflow { .. } => "integer overflow",
+            EvalError::MissingOperand { .. } => "missing operand",
+            EvalError::MissingOperator { .. } => "missing operator",
+            EvalError::UnknownFunction { .. } => "unknown function",
+            EvalError::WrongArgumentCount { .. } => "wrong number of arguments",
+            EvalError::InvalidArgument { .. } => "invalid argument",
+            EvalError::CyclicDefinition { .. } => "cyclic derived variable",
+            EvalError::IncompleteConditional { .. } => "incomplete conditional",
+            EvalError::InvalidCondition { .. } => "invalid condition",
+            EvalError::TooDeep { .. } => "expression too deep",
+            EvalError::TooLarge { .. } => "expression too large",
+            EvalError::MissingFeature { .. } => "missing crate feature",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self.error {
+            EvalError::Lex { kind: LexErrorKind::InvalidNumber, .. } => "numbers are written like 42, 4.2, 1_000, 6.02e23 or 0xff",
+            EvalError::Lex { kind: LexErrorKind::NumberOutOfRange, .. } => "integers must fit into a 64 bit integer, decimals into a 64 bit float",
+            EvalError::Lex { kind: LexErrorKind::UnexpectedCharacter(_), .. } => "remove the character or quote it inside a string literal",
+            EvalError::Lex { kind: LexErrorKind::UnterminatedString, .. } => "close the string with the same quote it was opened with",
+            EvalError::Lex { kind: LexErrorKind::InvalidPath, .. } => "paths are written like user.address.zip, items[0] or tags['env']",
+            EvalError::Lex { kind: LexErrorKind::InvalidNullTest, .. } => "'is' is only used in 'is null' and 'is not null'",
+            EvalError::Lex { kind: LexErrorKind::InvalidEscape, .. } => "supported escapes are \\n, \\t, \\\\, \\', \\\" and \\u{...}, use r'...' for raw strings",
+            EvalError::UnbalancedParenthesis { .. } => "every '(' needs a matching ')'",
+            EvalError::TypeMismatch { .. } => "the operator does not support this combination of operand types",
+            EvalError::UnsupportedOperand { .. } => "the operator does not support this operand type",
+            EvalError::ArityMismatch { .. } => "check that the operator is written between, in front of or after its operands",
+            EvalError::UnknownVariable { .. } => "provide a value for this variable in the context or a default with ??",
+            EvalError::DivisionByZero { .. } => "make sure the divisor can never be zero",
+            EvalError::Overflow { .. } => "the result does not fit into a 64 bit integer, consider using decimals or another overflow policy",
+            EvalError::MissingOperand { .. } => "an operator or parenthesis is missing one of its operands",
+            EvalError::MissingOperator { .. } => "two operands must be combined with an operator",
+            EvalError::UnknownFunction { .. } => "check the spelling of the function name",
+            EvalError::WrongArgumentCount { .. } => "add or remove arguments to match the function",
+            EvalError::InvalidArgument { .. } => "pass a value of the expected type",
+            EvalError::CyclicDefinition { .. } => "derived variables must not refer to themselves, directly or through other derived variables",
+            EvalError::IncompleteConditional { .. } => "conditionals are written like condition ? a : b or if condition then a else b",
+            EvalError::InvalidCondition { .. } => "the condition must be true, false or null, null chooses the else branch",
+            EvalError::TooDeep { .. } => "split the expression up or remove redundant parenthesis",
+            EvalError::TooLarge { .. } => "derived variables are copied into every place they are used, refer to large ones less often",
+            EvalError::MissingFeature { .. } => "enable the crate feature in Cargo.toml, decimal powers also work with the 'std' feature",
+        }
+    }
+}
+
+impl Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let source = self.source;
+        let span = self.error.span();
+
+        let start = floor_char_boundary(source, span.start);
+        let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[start..].find('\n').map_or(source.len(), |index| start + index);
+        // underline at least one character and never beyond the line the error starts in
+        let end = floor_char_boundary(source, min(max(span.end, start), line_end));
+
+        let line = source[line_start..line_end].trim_end();
+        let line_number = source[..line_start].matches('\n').count() + 1;
+        let column = source[line_start..start].chars().count();
+        let width = max(source[start..end].chars().count(), 1);
+
+        let gutter = line_number.to_string();
+        let padding = " ".repeat(gutter.len());
+
+        writeln!(f, "error: {}", self.title())?;
+        writeln!(f, "{} --> {}:{}", padding, line_number, column + 1)?;
+        writeln!(f, "{} |", padding)?;
+        writeln!(f, "{} | {}", gutter, line)?;
+        writeln!(f, "{} | {}{} {}", padding, " ".repeat(column), "^".repeat(width), self.error.message())?;
+        writeln!(f, "{} |", padding)?;
+        write!(f, "{} = hint: {}", padding, self.hint())
+    }
+}
+
+pub fn render_diagnostic(source: &str, error: &EvalError) -> String {
+    Diagnostic::new(source, error).to_string()
+}
+
+fn floor_char_boundary(source: &str, index: usize) -> usize {
+    let mut index = min(index, source.len());
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostic::render_diagnostic;
+    use crate::error::{EvalError, Span};
+    use crate::evaluate_expression;
+
+    #[test]
+    fn render_division_by_zero() {
+        let source = "1 + 4 / 0 ";
+        let error = evaluate_expression(source).unwrap_err();
+
+        let expected = "\
+error: division by zero
+  --> 1:5
+  |
+1 | 1 + 4 / 0
+  |     ^^^^^ division by zero
+  |
+  = hint: make sure the divisor can never be zero";
+
+        assert_eq!(render_diagnostic(source, &error), expected);
+    }
+
+    #[test]
+    fn render_error_in_second_line() {
+        let source = "a == 1 &&\nmissing";
+        let error = EvalError::UnknownVariable { name: "missing".into(), span: Span::new(10, 17) };
+
+        let rendered = render_diagnostic(source, &error);
+
+        assert!(rendered.contains("--> 2:1"));
+        assert!(rendered.contains("2 | missing\n  | ^^^^^^^ unknown variable 'missing'"));
+    }
+
+    #[test]
+    fn render_empty_span_at_end_of_input() {
+        let error = EvalError::MissingOperand { span: Span::new(5, 5) };
+
+        let rendered = render_diagnostic("3 + ", &error);
+
+        assert!(rendered.contains("1 | 3 +\n  |     ^ missing operand"));
+    }
+}
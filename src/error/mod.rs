@@ -0,0 +1,254 @@
+//!
+//! Errors reported while tokenizing, converting or evaluating an expression.
+//!
+//! Every error carries the [Span] of the expression source it refers to, so callers can point
+//! their users at the offending part of the input.
+
+use alloc::boxed::Box;
+use core::fmt::{Display, Formatter};
+use crate::definition::{LiteralType, Operator};
+use crate::function::ArgumentCount;
+
+
+/// Byte range `start..end` within the expression source.
+///
+/// An empty span at offset `0` is used when the position is not known.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub const fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum LexErrorKind {
+    InvalidNumber,
+    NumberOutOfRange,
+    UnexpectedCharacter(char),
+    UnterminatedString,
+    InvalidEscape,
+    InvalidPath,
+    InvalidNullTest,
+}
+
+impl Display for LexErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LexErrorKind::InvalidNumber => write!(f, "invalid number literal"),
+            LexErrorKind::NumberOutOfRange => write!(f, "number literal out of range"),
+            LexErrorKind::UnexpectedCharacter(character) => write!(f, "unexpected character {:?}", character),
+            LexErrorKind::UnterminatedString => write!(f, "unterminated string literal"),
+            LexErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            LexErrorKind::InvalidPath => write!(f, "invalid variable path"),
+            LexErrorKind::InvalidNullTest => write!(f, "invalid null test"),
+        }
+    }
+}
+
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum EvalError {
+    Lex { kind: LexErrorKind, span: Span },
+    UnbalancedParenthesis { span: Span },
+    TypeMismatch { operator: Operator, left: LiteralType, right: LiteralType, span: Span },
+    UnsupportedOperand { operator: Operator, operand: LiteralType, span: Span },
+    ArityMismatch { operator: Operator, span: Span },
+    UnknownVariable { name: Box<str>, span: Span },
+    DivisionByZero { span: Span },
+    Overflow { operator: Operator, span: Span },
+    MissingOperand { span: Span },
+    MissingOperator { span: Span },
+    UnknownFunction { name: Box<str>, span: Span },
+    WrongArgumentCount { name: Box<str>, expected: ArgumentCount, found: usize, span: Span },
+    InvalidArgument { index: usize, expected: &'static str, found: LiteralType, span: Span },
+    CyclicDefinition { name: Box<str>, span: Span },
+    IncompleteConditional { expected: &'static str, span: Span },
+    InvalidCondition { found: LiteralType, span: Span },
+    /// The expression is nested deeper than [crate::ast::MAX_DEPTH].
+    TooDeep { span: Span },
+    /// Expanding derived variables resulted in more than [crate::derived::MAX_EXPANDED_NODES] nodes.
+    TooLarge { span: Span },
+    /// The operation needs a crate feature which is not enabled, e.g. `9 ^ 0.5` without `math` or `std`.
+    MissingFeature { feature: &'static str, span: Span },
+}
+
+/// Errors found while parsing are reported with the same type as evaluation errors,
+/// so a single [crate::diagnostic::Diagnostic] can render both.
+pub type ParseError = EvalError;
+
+impl EvalError {
+    pub fn span(&self) -> Span {
+        match self {
+            EvalError::Lex { span, .. }
+            | EvalError::UnbalancedParenthesis { span }
+            | EvalError::TypeMismatch { span, .. }
+            | EvalError::UnsupportedOperand { span, .. }
+            | EvalError::ArityMismatch { span, .. }
+            | EvalError::UnknownVariable { span, .. }
+            | EvalError::DivisionByZero { span }
+            | EvalError::Overflow { span, .. }
+            | EvalError::MissingOperand { span }
+            | EvalError::MissingOperator { span }
+            | EvalError::UnknownFunction { span, .. }
+            | EvalError::WrongArgumentCount { span, .. }
+            | EvalError::InvalidArgument { span, .. }
+            | EvalError::CyclicDefinition { span, .. }
+            | EvalError::IncompleteConditional { span, .. }
+            | EvalError::InvalidCondition { span, .. }
+            | EvalError::TooDeep { span }
+            | EvalError::TooLarge { span }
+            | EvalError::MissingFeature { span, .. } => *span,
+        }
+    }
+
+    /// Error for a function argument of the wrong type, to be returned by
+    /// [crate::function::FunctionRegistry] functions. It is reported at the position of the argument.
+    pub fn invalid_argument(index: usize, expected: &'static str, found: LiteralType) -> Self {
+        EvalError::InvalidArgument { index, expected, found, span: Span::default() }
+    }
+
+    /// The error description without the position, e.g. for labels in a rendered report.
+    pub fn message(&self) -> ErrorMessage<'_> {
+        ErrorMessage(self)
+    }
+
+    /// Returns the same error located at the given span.
+    pub fn with_span(mut self, span: Span) -> Self {
+        *self.span_mut() = span;
+        self
+    }
+
+    fn span_mut(&mut self) -> &mut Span {
+        match self {
+            EvalError::Lex { span, .. }
+            | EvalError::UnbalancedParenthesis { span }
+            | EvalError::TypeMismatch { span, .. }
+            | EvalError::UnsupportedOperand { span, .. }
+            | EvalError::ArityMismatch { span, .. }
+            | EvalError::UnknownVariable { span, .. }
+            | EvalError::DivisionByZero { span }
+            | EvalError::Overflow { span, .. }
+            | EvalError::MissingOperand { span }
+            | EvalError::MissingOperator { span }
+            | EvalError::UnknownFunction { span, .. }
+            | EvalError::WrongArgumentCount { span, .. }
+            | EvalError::InvalidArgument { span, .. }
+            | EvalError::CyclicDefinition { span, .. }
+            | EvalError::IncompleteConditional { span, .. }
+            | EvalError::InvalidCondition { span, .. }
+            | EvalError::TooDeep { span }
+            | EvalError::TooLarge { span }
+            | EvalError::MissingFeature { span, .. } => span,
+        }
+    }
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at {}", self.message(), self.span())
+    }
+}
+
+/// Describes an [EvalError] without its position, see [EvalError::message].
+pub struct ErrorMessage<'a>(&'a EvalError);
+
+impl Display for ErrorMessage<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            EvalError::Lex { kind, .. } => {
+                write!(f, "{}", kind)
+            }
+            EvalError::UnbalancedParenthesis { .. } => {
+                write!(f, "unbalanced parenthesis")
+            }
+            EvalError::TypeMismatch { operator, left, right, .. } => {
+                write!(f, "operator {:?} is not applicable for {} and {}", operator, left, right)
+            }
+            EvalError::UnsupportedOperand { operator, operand, .. } => {
+                write!(f, "operator {:?} is not applicable for {}", operator, operand)
+            }
+            EvalError::ArityMismatch { operator, .. } => {
+                write!(f, "operator {:?} is used with the wrong number of operands", operator)
+            }
+            EvalError::UnknownVariable { name, .. } => {
+                write!(f, "unknown variable '{}'", name)
+            }
+            EvalError::DivisionByZero { .. } => {
+                write!(f, "division by zero")
+            }
+            EvalError::Overflow { operator, .. } => {
+                write!(f, "integer overflow in operator {:?}", operator)
+            }
+            EvalError::MissingOperand { .. } => {
+                write!(f, "missing operand")
+            }
+            EvalError::MissingOperator { .. } => {
+                write!(f, "expected an operator")
+            }
+            EvalError::UnknownFunction { name, .. } => {
+                write!(f, "unknown function '{}'", name)
+            }
+            EvalError::WrongArgumentCount { name, expected, found, .. } => {
+                write!(f, "function '{}' takes {} arguments but {} were given", name, expected, found)
+            }
+            EvalError::InvalidArgument { index, expected, found, .. } => {
+                write!(f, "argument {} must be {} but is {}", index + 1, expected, found)
+            }
+            EvalError::CyclicDefinition { name, .. } => {
+                write!(f, "derived variable '{}' depends on itself", name)
+            }
+            EvalError::IncompleteConditional { expected, .. } => {
+                write!(f, "incomplete conditional, expected '{}'", expected)
+            }
+            EvalError::InvalidCondition { found, .. } => {
+                write!(f, "condition must be a boolean but is {}", found)
+            }
+            EvalError::TooDeep { .. } => {
+                write!(f, "expression is nested more than {} levels deep", crate::ast::MAX_DEPTH)
+            }
+            EvalError::TooLarge { .. } => {
+                write!(f, "expression has more than {} nodes after expanding derived variables", crate::derived::MAX_EXPANDED_NODES)
+            }
+            EvalError::MissingFeature { feature, .. } => {
+                write!(f, "this operation needs the crate feature '{}'", feature)
+            }
+        }
+    }
+}
+
+impl core::error::Error for EvalError {}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+    use crate::definition::{LiteralType, Operator};
+    use crate::error::{EvalError, LexErrorKind, Span};
+
+    #[test]
+    fn display_contains_span() {
+        let error = EvalError::Lex { kind: LexErrorKind::InvalidNumber, span: Span::new(2, 7) };
+        assert_eq!(error.to_string(), "invalid number literal at 2..7");
+    }
+
+    #[test]
+    fn display_type_mismatch() {
+        let error = EvalError::TypeMismatch { operator: Operator::Plus, left: LiteralType::Boolean, right: LiteralType::Integer, span: Span::default() };
+        assert_eq!(error.to_string(), "operator Plus is not applicable for boolean and integer at 0..0");
+        assert_eq!(error.span(), Span::new(0, 0));
+    }
+}
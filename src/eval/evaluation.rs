@@ -1,51 +1,280 @@
-use crate::definition::Operator;
-use crate::Literal;
-
+use alloc::borrow::Cow;
 use alloc::boxed::Box;
+use alloc::format;
+use crate::definition::{LiteralType, Operator};
+use crate::error::{EvalError, Span};
+use crate::Literal;
+use crate::options::{Coercion, EvalOptions, IntegerOverflow, Modulo, NullLogic, ZeroDivision};
+#[cfg(feature = "decimal")]
+use rust_decimal::{Decimal, RoundingStrategy};
+#[cfg(feature = "decimal")]
+use crate::options::Rounding;
 
 pub struct OperatorExecutor {
     operator_command_factory: OperatorCommandFactory,
+    coercion: Coercion,
+    null_logic: NullLogic,
+    integer_overflow: IntegerOverflow,
+    zero_division: ZeroDivision,
+    #[cfg(feature = "decimal")]
+    money_scale: Option<u32>,
+    #[cfg(feature = "decimal")]
+    money_rounding: Rounding,
 }
 
 impl OperatorExecutor {
-    pub fn new() -> Self {
-        Self { operator_command_factory: OperatorCommandFactory {} }
+    pub fn new(options: &EvalOptions) -> Self {
+        let operator_command_factory = OperatorCommandFactory { euclidean: options.modulo == Modulo::Euclidean };
+        Self {
+            operator_command_factory,
+            coercion: options.coercion,
+            null_logic: options.null_logic,
+            integer_overflow: options.integer_overflow,
+            zero_division: options.zero_division,
+            #[cfg(feature = "decimal")]
+            money_scale: options.money_scale,
+            #[cfg(feature = "decimal")]
+            money_rounding: options.money_rounding,
+        }
+    }
+
+    pub fn execute(&self, operator: &Operator, right_side: &Literal, left_side: &Literal, span: Span) -> Result<Literal, EvalError> {
+        let (right_side, left_side) = self.coerce(operator, right_side, left_side);
+        let command = self.operator_command_factory.get_command(operator)
+            .ok_or(EvalError::ArityMismatch { operator: *operator, span })?;
+
+        let result = match (left_side.as_ref(), right_side.as_ref()) {
+            (Literal::Null, _) | (_, Literal::Null) => command.eval_null_operands(&left_side, &right_side, self.null_logic),
+            (Literal::Integer(left), Literal::Integer(right)) => command.execute_command(&right_side, &left_side)
+                .or_else(|error| self.overflowed(error, || command.eval_integer_overflow(*left, *right))),
+            _ => command.execute_command(&right_side, &left_side),
+        };
+        #[cfg(feature = "decimal")]
+        let result = result.map(|literal| self.rounded(literal));
+        result
+            .or_else(|error| self.divided_by_zero(error, command, &left_side, &right_side))
+            .map_err(|error| error.with_span(span))
+    }
+
+    /// Rounds money to the configured scale, other literals are kept.
+    #[cfg(feature = "decimal")]
+    fn rounded(&self, literal: Literal) -> Literal {
+        let strategy = match self.money_rounding {
+            Rounding::HalfEven => RoundingStrategy::MidpointNearestEven,
+            Rounding::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            Rounding::HalfDown => RoundingStrategy::MidpointTowardZero,
+            Rounding::Up => RoundingStrategy::AwayFromZero,
+            Rounding::Down => RoundingStrategy::ToZero,
+            Rounding::Ceiling => RoundingStrategy::ToPositiveInfinity,
+            Rounding::Floor => RoundingStrategy::ToNegativeInfinity,
+        };
+        match (literal, self.money_scale) {
+            (Literal::Money(value), Some(scale)) => Literal::Money(value.round_dp_with_strategy(scale, strategy)),
+            (literal, _) => literal,
+        }
+    }
+
+    /// Replaces an [EvalError::Overflow] by the result the [IntegerOverflow] policy asks for, other errors are kept.
+    fn overflowed(&self, error: EvalError, overflowed: impl FnOnce() -> Option<OverflowedInteger>) -> Result<Literal, EvalError> {
+        if !matches!(error, EvalError::Overflow { .. }) || self.integer_overflow == IntegerOverflow::Error {
+            return Err(error);
+        }
+        match (overflowed(), self.integer_overflow) {
+            (Some(overflowed), IntegerOverflow::Wrapping) => Ok(Literal::Integer(overflowed.wrapped)),
+            (Some(overflowed), IntegerOverflow::Saturating) if overflowed.exact > 0.0 => Ok(Literal::Integer(i64::MAX)),
+            (Some(_), IntegerOverflow::Saturating) => Ok(Literal::Integer(i64::MIN)),
+            (Some(overflowed), IntegerOverflow::PromoteToDecimal) => Ok(Literal::Decimal(overflowed.exact)),
+            _ => Err(error),
+        }
+    }
+
+    /// Replaces an [EvalError::DivisionByZero] by the result the [ZeroDivision] policy asks for, other errors are kept.
+    fn divided_by_zero(&self, error: EvalError, command: &dyn OperatorCommand, left_side: &Literal, right_side: &Literal) -> Result<Literal, EvalError> {
+        if !matches!(error, EvalError::DivisionByZero { .. }) {
+            return Err(error);
+        }
+        match (self.zero_division, to_decimal(left_side), to_decimal(right_side)) {
+            (ZeroDivision::Error, _, _) => Err(error),
+            (ZeroDivision::Null, _, _) => Ok(Literal::Null),
+            (ZeroDivision::Ieee, Some(left), Some(right)) => command.eval_ieee_division_by_zero(left, right)
+                .map(Literal::Decimal)
+                .ok_or(error),
+            (ZeroDivision::Ieee, _, _) => Err(error),
+        }
+    }
+
+    /// Converts the operands according to the [Coercion] table, integer and decimal pairs are left to the command.
+    fn coerce<'a>(&self, operator: &Operator, right_side: &'a Literal, left_side: &'a Literal) -> (Cow<'a, Literal>, Cow<'a, Literal>) {
+        if self.coercion == Coercion::Lenient && *operator == Operator::Plus {
+            match (left_side, right_side) {
+                (Literal::String(_), Literal::Integer(_) | Literal::Decimal(_) | Literal::Boolean(_)) => {
+                    return (Cow::Owned(to_string_literal(right_side)), Cow::Borrowed(left_side));
+                }
+                (Literal::Integer(_) | Literal::Decimal(_) | Literal::Boolean(_), Literal::String(_)) => {
+                    return (Cow::Borrowed(right_side), Cow::Owned(to_string_literal(left_side)));
+                }
+                _ => {}
+            }
+        }
+        (Cow::Borrowed(right_side), Cow::Borrowed(left_side))
     }
 
-    pub fn execute(&self, operator: &Operator, right_side: &Literal, left_side: &Literal) -> Result<Literal, &'static str> {
-        self.operator_command_factory.get_command(operator).execute_command(right_side, left_side)
+    pub fn execute_unary(&self, operator: &Operator, operand: &Literal, span: Span) -> Result<Literal, EvalError> {
+        let command = self.operator_command_factory.get_unary_command(operator)
+            .ok_or(EvalError::ArityMismatch { operator: *operator, span })?;
+
+        let result = match operand {
+            Literal::Null => command.eval_null_operand(self.null_logic),
+            Literal::Integer(value) => command.execute_command(operand)
+                .or_else(|error| self.overflowed(error, || command.eval_integer_overflow(*value))),
+            _ => command.execute_command(operand),
+        };
+        result.map_err(|error| error.with_span(span))
     }
 }
 
 
-pub struct OperatorCommandFactory {}
+/// An integer operation whose result does not fit into an i64, described by the wrapped result and by the
+/// result calculated with decimals, whose sign is always exact.
+pub struct OverflowedInteger {
+    wrapped: i64,
+    exact: f64,
+}
+
+impl OverflowedInteger {
+    fn new(wrapped: i64, exact: f64) -> Self {
+        Self { wrapped, exact }
+    }
+}
+
+/// `base ^ exponent` by repeated squaring, for exponents beyond the u32 taken by the standard pow functions.
+fn pow_by_squaring<T: Copy>(mut base: T, mut exponent: u64, one: T, multiply: impl Fn(T, T) -> T) -> T {
+    let mut result = one;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply(result, base);
+        }
+        base = multiply(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn to_decimal(literal: &Literal) -> Option<f64> {
+    match literal {
+        Literal::Integer(value) => Some(*value as f64),
+        Literal::Decimal(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Both operands as money if one of them is money and the other one money or an integer.
+#[cfg(feature = "decimal")]
+fn to_money(left_side: &Literal, right_side: &Literal) -> Option<(Decimal, Decimal)> {
+    match (left_side, right_side) {
+        (Literal::Money(left), Literal::Money(right)) => Some((*left, *right)),
+        (Literal::Money(left), Literal::Integer(right)) => Some((*left, Decimal::from(*right))),
+        (Literal::Integer(left), Literal::Money(right)) => Some((Decimal::from(*left), *right)),
+        _ => None,
+    }
+}
+
+fn to_string_literal(literal: &Literal) -> Literal {
+    Literal::String(Box::from(format!("{}", literal)))
+}
+
+fn null_as_false(literal: &Literal) -> Literal {
+    match literal {
+        Literal::Null => Literal::Boolean(false),
+        _ => literal.clone(),
+    }
+}
+
+/// SQL logic for `&&` and `||` with a null operand, a boolean operand equal to `decisive` decides the result,
+/// otherwise it is unknown and therefore null.
+fn three_valued_logic(command: &dyn OperatorCommand, left_side: &Literal, right_side: &Literal, decisive: bool) -> Result<Literal, EvalError> {
+    match (left_side, right_side) {
+        (Literal::Boolean(value), Literal::Null) | (Literal::Null, Literal::Boolean(value)) if *value == decisive => {
+            Ok(Literal::Boolean(decisive))
+        }
+        (Literal::Boolean(_) | Literal::Null, Literal::Boolean(_) | Literal::Null) => Ok(Literal::Null),
+        _ => Err(command.type_mismatch(left_side.literal_type(), right_side.literal_type())),
+    }
+}
+
+/// The result of `<`, `<=`, `>` and `>=` with a null operand.
+fn null_comparison(null_logic: NullLogic) -> Literal {
+    match null_logic {
+        NullLogic::ThreeValued => Literal::Null,
+        NullLogic::NullIsFalse => Literal::Boolean(false),
+    }
+}
+
+
+pub struct OperatorCommandFactory {
+    euclidean: bool,
+}
 
 
 
 impl OperatorCommandFactory {
-    pub fn get_command(&self, operator: &Operator) -> &dyn OperatorCommand {
+    pub fn get_command(&self, operator: &Operator) -> Option<&dyn OperatorCommand> {
+        match operator {
+            Operator::Or => { Some(&OrCommand {}) }
+            Operator::And => { Some(&AndCommand {}) }
+            Operator::NotEqual => { Some(&NotEqualCommand {}) }
+            Operator::Equal => { Some(&EqualCommand {}) }
+            Operator::Greater => { Some(&GreaterCommand {}) }
+            Operator::GreaterOrEqual => { Some(&GreaterOrEqualCommand {}) }
+            Operator::Less => { Some(&LessCommand {}) }
+            Operator::LessOrEqual => { Some(&LessOrEqualCommand {}) }
+            Operator::Plus => { Some(&PlusCommand {}) }
+            Operator::Minus => { Some(&MinusCommand {}) }
+            Operator::Divide => { Some(&DivideCommand {}) }
+            Operator::Multiply => { Some(&MultiplyCommand {}) }
+            Operator::Modulo if self.euclidean => { Some(&ModuloCommand { euclidean: true }) }
+            Operator::Modulo => { Some(&ModuloCommand { euclidean: false }) }
+            Operator::IntegerDivide if self.euclidean => { Some(&IntegerDivideCommand { euclidean: true }) }
+            Operator::IntegerDivide => { Some(&IntegerDivideCommand { euclidean: false }) }
+            Operator::BitAnd => { Some(&BitAndCommand {}) }
+            Operator::BitOr => { Some(&BitOrCommand {}) }
+            Operator::BitXor => { Some(&BitXorCommand {}) }
+            Operator::ShiftLeft => { Some(&ShiftLeftCommand {}) }
+            Operator::ShiftRight => { Some(&ShiftRightCommand {}) }
+            Operator::PowerOf => { Some(&PowerOfCommand {}) }
+            Operator::Coalesce => { Some(&CoalesceCommand {}) }
+            Operator::Not | Operator::UnaryMinus | Operator::UnaryPlus | Operator::BitNot => { None }
+            Operator::IsNull | Operator::IsNotNull => { None }
+        }
+    }
+
+    pub fn get_unary_command(&self, operator: &Operator) -> Option<&dyn UnaryOperatorCommand> {
         match operator {
-            Operator::Or => { &OrCommand {} }
-            Operator::And => { &AndCommand {} }
-            Operator::Not => { &NotCommand {} }
-            Operator::NotEqual => { &NotEqualCommand {} }
-            Operator::Equal => { &EqualCommand {} }
-            Operator::Greater => { &GreaterCommand {} }
-            Operator::GreaterOrEqual => { &GreaterOrEqualCommand {} }
-            Operator::Less => { &LessCommand {} }
-            Operator::LessOrEqual => { &LessOrEqualCommand {} }
-            Operator::Plus => { &PlusCommand {} }
-            Operator::Minus => { &MinusCommand {} }
-            Operator::Divide => { &DivideCommand {} }
-            Operator::Multiply => { &MultiplyCommand {} }
-            Operator::PowerOf => { &PowerOfCommand {} }
+            Operator::Not => { Some(&NotCommand {}) }
+            Operator::UnaryMinus => { Some(&UnaryMinusCommand {}) }
+            Operator::UnaryPlus => { Some(&UnaryPlusCommand {}) }
+            Operator::BitNot => { Some(&BitNotCommand {}) }
+            Operator::IsNull => { Some(&IsNullCommand { negated: false }) }
+            Operator::IsNotNull => { Some(&IsNullCommand { negated: true }) }
+            _ => { None }
         }
     }
 }
 
 pub trait OperatorCommand {
-    fn execute_command(&self, right_side: &Literal, left_side: &Literal) -> Result<Literal, &'static str> {
-        if let (Literal::Integer(left), Literal::Integer(right)) = (&left_side, &right_side) {
+    fn operator(&self) -> Operator;
+
+    fn execute_command(&self, right_side: &Literal, left_side: &Literal) -> Result<Literal, EvalError> {
+        // mixed operands are converted first, the error names the types as they were written
+        let as_written = |error| match error {
+            EvalError::TypeMismatch { .. } => self.type_mismatch(left_side.literal_type(), right_side.literal_type()),
+            error => error,
+        };
+        #[cfg(feature = "decimal")]
+        if let Some((left, right)) = to_money(left_side, right_side) {
+            return self.eval_money_literals(left, right).map_err(as_written);
+        }
+        let result = if let (Literal::Integer(left), Literal::Integer(right)) = (&left_side, &right_side) {
             self.eval_integer_literals(*left, *right)
         } else if let (Literal::Decimal(left), Literal::Decimal(right)) = (&left_side, &right_side) {
             // both are decimals
@@ -62,97 +291,349 @@ pub trait OperatorCommand {
             self.eval_boolean_literals(*left, *right)
         } else if let (Literal::String(left), Literal::String(right)) = (&left_side, &right_side) {
             // both are strings
-            self.eval_string_literals(&left, &right)
+            self.eval_string_literals(left, right)
         } else {
-            Err("Either the left or the right literal is not supported")
+            Err(self.type_mismatch(left_side.literal_type(), right_side.literal_type()))
+        };
+        result.map_err(as_written)
+    }
+
+    /// Called instead of [OperatorCommand::execute_command] if at least one operand is null, the result is null
+    /// unless the command knows better, see [NullLogic].
+    fn eval_null_operands(&self, _left_side: &Literal, _right_side: &Literal, _null_logic: NullLogic) -> Result<Literal, EvalError> {
+        Ok(Literal::Null)
+    }
+
+    /// Called if [OperatorCommand::eval_integer_literals] overflowed, to apply a policy other than
+    /// [IntegerOverflow::Error]. Commands which report other problems as overflow, like shifts by more than
+    /// 63 bits, return `None` and keep the error.
+    fn eval_integer_overflow(&self, _left: i64, _right: i64) -> Option<OverflowedInteger> {
+        None
+    }
+
+    /// The IEEE 754 result of a command which reported [EvalError::DivisionByZero], used with [ZeroDivision::Ieee].
+    fn eval_ieee_division_by_zero(&self, _left: f64, _right: f64) -> Option<f64> {
+        None
+    }
+
+    fn type_mismatch(&self, left: LiteralType, right: LiteralType) -> EvalError {
+        EvalError::TypeMismatch { operator: self.operator(), left, right, span: Span::default() }
+    }
+
+    fn overflow(&self) -> EvalError {
+        EvalError::Overflow { operator: self.operator(), span: Span::default() }
+    }
+
+    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, EvalError>;
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError>;
+
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError>;
+
+    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, EvalError>;
+
+    /// Called if one operand is money and the other one money or an integer, most operators do not support money.
+    /// An integer operand is already converted, a type mismatch is reported with the types as written.
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, _left: Decimal, _right: Decimal) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Money, LiteralType::Money))
+    }
+}
+
+pub trait UnaryOperatorCommand {
+    fn operator(&self) -> Operator;
+
+    fn execute_command(&self, operand: &Literal) -> Result<Literal, EvalError> {
+        match operand {
+            Literal::Boolean(value) => self.eval_boolean_literal(*value),
+            Literal::Integer(value) => self.eval_integer_literal(*value),
+            Literal::Decimal(value) => self.eval_decimal_literal(*value),
+            Literal::String(value) => self.eval_string_literal(value),
+            Literal::Null => self.eval_null_operand(NullLogic::default()),
+            Literal::List(_) => Err(self.unsupported_operand(LiteralType::List)),
+            #[cfg(feature = "decimal")]
+            Literal::Money(value) => self.eval_money_literal(*value),
         }
     }
 
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str>;
+    /// The result for a null operand, null unless the command knows better, see [NullLogic].
+    fn eval_null_operand(&self, _null_logic: NullLogic) -> Result<Literal, EvalError> {
+        Ok(Literal::Null)
+    }
+
+    /// Called if [UnaryOperatorCommand::eval_integer_literal] overflowed, see [OperatorCommand::eval_integer_overflow].
+    fn eval_integer_overflow(&self, _value: i64) -> Option<OverflowedInteger> {
+        None
+    }
+
+    fn unsupported_operand(&self, operand: LiteralType) -> EvalError {
+        EvalError::UnsupportedOperand { operator: self.operator(), operand, span: Span::default() }
+    }
+
+    fn eval_boolean_literal(&self, value: bool) -> Result<Literal, EvalError>;
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str>;
+    fn eval_integer_literal(&self, value: i64) -> Result<Literal, EvalError>;
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str>;
+    fn eval_decimal_literal(&self, value: f64) -> Result<Literal, EvalError>;
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str>;
+    fn eval_string_literal(&self, value: &str) -> Result<Literal, EvalError>;
+
+    #[cfg(feature = "decimal")]
+    fn eval_money_literal(&self, _value: Decimal) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::Money))
+    }
 }
 
-struct OrCommand {}
+struct NotCommand {}
 
-impl OperatorCommand for OrCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
-        Ok(Literal::Boolean(left || right))
+impl UnaryOperatorCommand for NotCommand {
+    fn operator(&self) -> Operator {
+        Operator::Not
+    }
+
+    fn eval_null_operand(&self, null_logic: NullLogic) -> Result<Literal, EvalError> {
+        match null_logic {
+            NullLogic::ThreeValued => Ok(Literal::Null),
+            NullLogic::NullIsFalse => Ok(Literal::Boolean(true)),
+        }
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
-        Err("<left || right> is not applicable for integers!")
+    fn eval_boolean_literal(&self, value: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(!value))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
-        Err("<left || right> is not applicable for integers!")
+    fn eval_integer_literal(&self, _value: i64) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::Integer))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
-        Err("<left || right> is not applicable for integers!")
+    fn eval_decimal_literal(&self, _value: f64) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::Decimal))
+    }
+
+    fn eval_string_literal(&self, _value: &str) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::String))
     }
 }
 
-struct AndCommand {}
+struct UnaryMinusCommand {}
 
-impl OperatorCommand for AndCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
-        Ok(Literal::Boolean(left && right))
+impl UnaryOperatorCommand for UnaryMinusCommand {
+    fn operator(&self) -> Operator {
+        Operator::UnaryMinus
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
-        Err("<left && right> is not applicable for integers!")
+    fn eval_integer_overflow(&self, value: i64) -> Option<OverflowedInteger> {
+        Some(OverflowedInteger::new(value.wrapping_neg(), -(value as f64)))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
-        Err("<left && right> is not applicable for decimals!")
+    fn eval_boolean_literal(&self, _value: bool) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::Boolean))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
-        Err("<left && right> is not applicable for strings!")
+    fn eval_integer_literal(&self, value: i64) -> Result<Literal, EvalError> {
+        value.checked_neg()
+            .map(Literal::Integer)
+            .ok_or(EvalError::Overflow { operator: self.operator(), span: Span::default() })
+    }
+
+    fn eval_decimal_literal(&self, value: f64) -> Result<Literal, EvalError> {
+        Ok(Literal::Decimal(-value))
+    }
+
+    fn eval_string_literal(&self, _value: &str) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::String))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn eval_money_literal(&self, value: Decimal) -> Result<Literal, EvalError> {
+        Ok(Literal::Money(-value))
     }
 }
 
-struct NotCommand {}
+struct UnaryPlusCommand {}
+
+impl UnaryOperatorCommand for UnaryPlusCommand {
+    fn operator(&self) -> Operator {
+        Operator::UnaryPlus
+    }
+
+    fn eval_boolean_literal(&self, _value: bool) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::Boolean))
+    }
+
+    fn eval_integer_literal(&self, value: i64) -> Result<Literal, EvalError> {
+        Ok(Literal::Integer(value))
+    }
+
+    fn eval_decimal_literal(&self, value: f64) -> Result<Literal, EvalError> {
+        Ok(Literal::Decimal(value))
+    }
+
+    fn eval_string_literal(&self, _value: &str) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::String))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn eval_money_literal(&self, value: Decimal) -> Result<Literal, EvalError> {
+        Ok(Literal::Money(value))
+    }
+}
+
+/// `operand is null` and `operand is not null`, which are true or false for every operand.
+struct IsNullCommand {
+    negated: bool,
+}
+
+impl UnaryOperatorCommand for IsNullCommand {
+    fn operator(&self) -> Operator {
+        if self.negated { Operator::IsNotNull } else { Operator::IsNull }
+    }
+
+    fn execute_command(&self, operand: &Literal) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean((*operand == Literal::Null) != self.negated))
+    }
+
+    fn eval_null_operand(&self, _null_logic: NullLogic) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(!self.negated))
+    }
+
+    fn eval_boolean_literal(&self, _value: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(self.negated))
+    }
+
+    fn eval_integer_literal(&self, _value: i64) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(self.negated))
+    }
+
+    fn eval_decimal_literal(&self, _value: f64) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(self.negated))
+    }
+
+    fn eval_string_literal(&self, _value: &str) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(self.negated))
+    }
+}
+
+struct BitNotCommand {}
+
+impl UnaryOperatorCommand for BitNotCommand {
+    fn operator(&self) -> Operator {
+        Operator::BitNot
+    }
+
+    fn eval_boolean_literal(&self, _value: bool) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::Boolean))
+    }
+
+    fn eval_integer_literal(&self, value: i64) -> Result<Literal, EvalError> {
+        Ok(Literal::Integer(!value))
+    }
+
+    fn eval_decimal_literal(&self, _value: f64) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::Decimal))
+    }
 
-impl OperatorCommand for NotCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
-        Err("<!> is not applicable for two booleans!")
+    fn eval_string_literal(&self, _value: &str) -> Result<Literal, EvalError> {
+        Err(self.unsupported_operand(LiteralType::String))
+    }
+}
+
+struct OrCommand {}
+
+impl OperatorCommand for OrCommand {
+    fn operator(&self) -> Operator {
+        Operator::Or
+    }
+
+    /// `true || null` is true, any other combination with a boolean is null or uses null as false.
+    fn eval_null_operands(&self, left_side: &Literal, right_side: &Literal, null_logic: NullLogic) -> Result<Literal, EvalError> {
+        match null_logic {
+            NullLogic::ThreeValued => three_valued_logic(self, left_side, right_side, true),
+            NullLogic::NullIsFalse => self.execute_command(&null_as_false(right_side), &null_as_false(left_side)),
+        }
+    }
+
+    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(left || right))
+    }
+
+    fn eval_integer_literals(&self, _left: i64, _right: i64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Integer, LiteralType::Integer))
+    }
+
+    fn eval_decimal_literals(&self, _left: f64, _right: f64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Decimal, LiteralType::Decimal))
+    }
+
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+}
+
+struct AndCommand {}
+
+impl OperatorCommand for AndCommand {
+    fn operator(&self) -> Operator {
+        Operator::And
+    }
+
+    /// `false && null` is false, any other combination with a boolean is null or uses null as false.
+    fn eval_null_operands(&self, left_side: &Literal, right_side: &Literal, null_logic: NullLogic) -> Result<Literal, EvalError> {
+        match null_logic {
+            NullLogic::ThreeValued => three_valued_logic(self, left_side, right_side, false),
+            NullLogic::NullIsFalse => self.execute_command(&null_as_false(right_side), &null_as_false(left_side)),
+        }
+    }
+
+    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(left && right))
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
-        Err("<!> is not applicable for two integers!")
+    fn eval_integer_literals(&self, _left: i64, _right: i64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Integer, LiteralType::Integer))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
-        Err("<!> is not applicable for two decimals!")
+    fn eval_decimal_literals(&self, _left: f64, _right: f64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Decimal, LiteralType::Decimal))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
-        Err("<!> is not applicable for two strings!")
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
     }
 }
 
 struct NotEqualCommand {}
 
 impl OperatorCommand for NotEqualCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
+    fn operator(&self) -> Operator {
+        Operator::NotEqual
+    }
+
+    fn eval_null_operands(&self, left_side: &Literal, right_side: &Literal, null_logic: NullLogic) -> Result<Literal, EvalError> {
+        match null_logic {
+            NullLogic::ThreeValued => Ok(Literal::Null),
+            NullLogic::NullIsFalse => Ok(Literal::Boolean(left_side != right_side)),
+        }
+    }
+
+    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left != right))
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left != right))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left != right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
+    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(left != right))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left != right))
     }
 }
@@ -160,19 +641,35 @@ impl OperatorCommand for NotEqualCommand {
 struct EqualCommand {}
 
 impl OperatorCommand for EqualCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
+    fn operator(&self) -> Operator {
+        Operator::Equal
+    }
+
+    fn eval_null_operands(&self, left_side: &Literal, right_side: &Literal, null_logic: NullLogic) -> Result<Literal, EvalError> {
+        match null_logic {
+            NullLogic::ThreeValued => Ok(Literal::Null),
+            NullLogic::NullIsFalse => Ok(Literal::Boolean(left_side == right_side)),
+        }
+    }
+
+    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(left == right))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left == right))
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left == right))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left == right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left == right))
     }
 }
@@ -180,19 +677,32 @@ impl OperatorCommand for EqualCommand {
 struct GreaterCommand {}
 
 impl OperatorCommand for GreaterCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
+    fn operator(&self) -> Operator {
+        Operator::Greater
+    }
+
+    fn eval_null_operands(&self, _left_side: &Literal, _right_side: &Literal, null_logic: NullLogic) -> Result<Literal, EvalError> {
+        Ok(null_comparison(null_logic))
+    }
+
+    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(left & !right))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left > right))
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left > right))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left > right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left > right))
     }
 }
@@ -200,19 +710,32 @@ impl OperatorCommand for GreaterCommand {
 struct GreaterOrEqualCommand {}
 
 impl OperatorCommand for GreaterOrEqualCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
+    fn operator(&self) -> Operator {
+        Operator::GreaterOrEqual
+    }
+
+    fn eval_null_operands(&self, _left_side: &Literal, _right_side: &Literal, null_logic: NullLogic) -> Result<Literal, EvalError> {
+        Ok(null_comparison(null_logic))
+    }
+
+    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(left | !right))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left >= right))
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left >= right))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left >= right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left >= right))
     }
 }
@@ -220,19 +743,32 @@ impl OperatorCommand for GreaterOrEqualCommand {
 struct LessCommand {}
 
 impl OperatorCommand for LessCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
+    fn operator(&self) -> Operator {
+        Operator::Less
+    }
+
+    fn eval_null_operands(&self, _left_side: &Literal, _right_side: &Literal, null_logic: NullLogic) -> Result<Literal, EvalError> {
+        Ok(null_comparison(null_logic))
+    }
+
+    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(!left & right))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left < right))
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left < right))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left < right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left < right))
     }
 }
@@ -240,19 +776,32 @@ impl OperatorCommand for LessCommand {
 struct LessOrEqualCommand {}
 
 impl OperatorCommand for LessOrEqualCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
+    fn operator(&self) -> Operator {
+        Operator::LessOrEqual
+    }
+
+    fn eval_null_operands(&self, _left_side: &Literal, _right_side: &Literal, null_logic: NullLogic) -> Result<Literal, EvalError> {
+        Ok(null_comparison(null_logic))
+    }
+
+    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(!left | right))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left <= right))
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left <= right))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left <= right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
         Ok(Literal::Boolean(left <= right))
     }
 }
@@ -260,103 +809,503 @@ impl OperatorCommand for LessOrEqualCommand {
 struct PlusCommand {}
 
 impl OperatorCommand for PlusCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
-        Err("<left + right> is not applicable for booleans!")
+    fn operator(&self) -> Operator {
+        Operator::Plus
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
-        Ok(Literal::Integer(left + right))
+    fn eval_integer_overflow(&self, left: i64, right: i64) -> Option<OverflowedInteger> {
+        Some(OverflowedInteger::new(left.wrapping_add(right), left as f64 + right as f64))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        left.checked_add(right)
+            .map(Literal::Integer)
+            .ok_or_else(|| self.overflow())
+    }
+
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
         Ok(Literal::Decimal(left + right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
-        Err("<left + right> is not applicable for two strings!")
-        //TODO check if supported
-        // Ok(Literal::String(Box::from(format!("{}{}", left, right))))
+    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, EvalError> {
+        Ok(Literal::String(Box::from(format!("{}{}", left, right))))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
+        left.checked_add(right)
+            .map(Literal::Money)
+            .ok_or_else(|| self.overflow())
     }
 }
 
 struct MinusCommand {}
 
 impl OperatorCommand for MinusCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
-        Err("<left - right> is not applicable for booleans!")
+    fn operator(&self) -> Operator {
+        Operator::Minus
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
-        Ok(Literal::Integer(left - right))
+    fn eval_integer_overflow(&self, left: i64, right: i64) -> Option<OverflowedInteger> {
+        Some(OverflowedInteger::new(left.wrapping_sub(right), left as f64 - right as f64))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        left.checked_sub(right)
+            .map(Literal::Integer)
+            .ok_or_else(|| self.overflow())
+    }
+
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
         Ok(Literal::Decimal(left - right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
-        Err("<left - right> is not applicable for two strings!")
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
+        left.checked_sub(right)
+            .map(Literal::Money)
+            .ok_or_else(|| self.overflow())
     }
 }
 
+/// `left / right` is exact, integers which divide evenly stay integers and any other quotient is a decimal,
+/// `//` is the integer division.
 struct DivideCommand {}
 
 impl OperatorCommand for DivideCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
-        Err("<left / right> is not applicable for booleans!")
+    fn operator(&self) -> Operator {
+        Operator::Divide
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
-        //TODO do we always want an integer or better transform to decimal???
-        Ok(Literal::Integer(left / right))
+    fn eval_integer_overflow(&self, left: i64, right: i64) -> Option<OverflowedInteger> {
+        Some(OverflowedInteger::new(left.wrapping_div(right), left as f64 / right as f64))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_ieee_division_by_zero(&self, left: f64, right: f64) -> Option<f64> {
+        Some(left / right)
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        if right == 0 {
+            return Err(EvalError::DivisionByZero { span: Span::default() });
+        }
+        // i64::MIN % -1 has no checked remainder, but its quotient overflows anyway
+        match left.checked_rem(right) {
+            Some(0) | None => left.checked_div(right)
+                .map(Literal::Integer)
+                .ok_or_else(|| self.overflow()),
+            Some(_) => Ok(Literal::Decimal(left as f64 / right as f64)),
+        }
+    }
+
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
+        if right == 0.0 {
+            return Err(EvalError::DivisionByZero { span: Span::default() });
+        }
         Ok(Literal::Decimal(left / right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
-        Err("<left / right > is not applicable for two strings!")
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
+        if right.is_zero() {
+            return Err(EvalError::DivisionByZero { span: Span::default() });
+        }
+        left.checked_div(right)
+            .map(Literal::Money)
+            .ok_or_else(|| self.overflow())
     }
 }
 
 struct MultiplyCommand {}
 
 impl OperatorCommand for MultiplyCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
-        Err("<left * right> is not applicable for booleans!")
+    fn operator(&self) -> Operator {
+        Operator::Multiply
+    }
+
+    fn eval_integer_overflow(&self, left: i64, right: i64) -> Option<OverflowedInteger> {
+        Some(OverflowedInteger::new(left.wrapping_mul(right), left as f64 * right as f64))
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
-        Ok(Literal::Integer(left * right))
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        left.checked_mul(right)
+            .map(Literal::Integer)
+            .ok_or_else(|| self.overflow())
+    }
+
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
         Ok(Literal::Decimal(left * right))
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
-        Err("<left * right> is not applicable for two strings!")
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
+        left.checked_mul(right)
+            .map(Literal::Money)
+            .ok_or_else(|| self.overflow())
+    }
+}
+
+/// `left % right`, see [Modulo] for the sign of the result.
+struct ModuloCommand {
+    euclidean: bool,
+}
+
+impl OperatorCommand for ModuloCommand {
+    fn operator(&self) -> Operator {
+        Operator::Modulo
+    }
+
+    fn eval_ieee_division_by_zero(&self, left: f64, right: f64) -> Option<f64> {
+        Some(left % right)
+    }
+
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        if right == 0 {
+            return Err(EvalError::DivisionByZero { span: Span::default() });
+        }
+        // i64::MIN % -1 is 0, only the quotient overflows
+        match self.euclidean {
+            true => Ok(Literal::Integer(left.wrapping_rem_euclid(right))),
+            false => Ok(Literal::Integer(left.wrapping_rem(right))),
+        }
+    }
+
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
+        if right == 0.0 {
+            return Err(EvalError::DivisionByZero { span: Span::default() });
+        }
+        let remainder = left % right;
+        match self.euclidean && remainder < 0.0 {
+            true => Ok(Literal::Decimal(remainder + right.abs())),
+            false => Ok(Literal::Decimal(remainder)),
+        }
+    }
+
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+
+    #[cfg(feature = "decimal")]
+    fn eval_money_literals(&self, left: Decimal, right: Decimal) -> Result<Literal, EvalError> {
+        if right.is_zero() {
+            return Err(EvalError::DivisionByZero { span: Span::default() });
+        }
+        let remainder = left.checked_rem(right).ok_or_else(|| self.overflow())?;
+        match self.euclidean && remainder.is_sign_negative() && !remainder.is_zero() {
+            true => Ok(Literal::Money(remainder + right.abs())),
+            false => Ok(Literal::Money(remainder)),
+        }
     }
 }
 
+/// `left // right`, the quotient of two integers rounded as described by [Modulo].
+struct IntegerDivideCommand {
+    euclidean: bool,
+}
+
+impl OperatorCommand for IntegerDivideCommand {
+    fn operator(&self) -> Operator {
+        Operator::IntegerDivide
+    }
+
+    fn eval_ieee_division_by_zero(&self, left: f64, right: f64) -> Option<f64> {
+        Some(left / right)
+    }
+
+    fn eval_integer_overflow(&self, left: i64, right: i64) -> Option<OverflowedInteger> {
+        // only i64::MIN // -1 overflows, which is the same in both rounding modes
+        Some(OverflowedInteger::new(left.wrapping_div(right), left as f64 / right as f64))
+    }
+
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        if right == 0 {
+            return Err(EvalError::DivisionByZero { span: Span::default() });
+        }
+        let quotient = match self.euclidean {
+            true => left.checked_div_euclid(right),
+            false => left.checked_div(right),
+        };
+        quotient
+            .map(Literal::Integer)
+            .ok_or_else(|| self.overflow())
+    }
+
+    fn eval_decimal_literals(&self, _left: f64, _right: f64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Decimal, LiteralType::Decimal))
+    }
+
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+}
+
+struct BitAndCommand {}
+
+impl OperatorCommand for BitAndCommand {
+    fn operator(&self) -> Operator {
+        Operator::BitAnd
+    }
+
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        Ok(Literal::Integer(left & right))
+    }
+
+    fn eval_decimal_literals(&self, _left: f64, _right: f64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Decimal, LiteralType::Decimal))
+    }
+
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+}
+
+struct BitOrCommand {}
+
+impl OperatorCommand for BitOrCommand {
+    fn operator(&self) -> Operator {
+        Operator::BitOr
+    }
+
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        Ok(Literal::Integer(left | right))
+    }
+
+    fn eval_decimal_literals(&self, _left: f64, _right: f64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Decimal, LiteralType::Decimal))
+    }
+
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+}
+
+struct BitXorCommand {}
+
+impl OperatorCommand for BitXorCommand {
+    fn operator(&self) -> Operator {
+        Operator::BitXor
+    }
+
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        Ok(Literal::Integer(left ^ right))
+    }
+
+    fn eval_decimal_literals(&self, _left: f64, _right: f64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Decimal, LiteralType::Decimal))
+    }
+
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+}
+
+struct ShiftLeftCommand {}
+
+impl OperatorCommand for ShiftLeftCommand {
+    fn operator(&self) -> Operator {
+        Operator::ShiftLeft
+    }
+
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        u32::try_from(right).ok()
+            .and_then(|shift| left.checked_shl(shift))
+            .map(Literal::Integer)
+            .ok_or_else(|| self.overflow())
+    }
+
+    fn eval_decimal_literals(&self, _left: f64, _right: f64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Decimal, LiteralType::Decimal))
+    }
+
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+}
+
+struct ShiftRightCommand {}
+
+impl OperatorCommand for ShiftRightCommand {
+    fn operator(&self) -> Operator {
+        Operator::ShiftRight
+    }
+
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        // arithmetic shift, the sign bit is kept
+        u32::try_from(right).ok()
+            .and_then(|shift| left.checked_shr(shift))
+            .map(Literal::Integer)
+            .ok_or_else(|| self.overflow())
+    }
+
+    fn eval_decimal_literals(&self, _left: f64, _right: f64) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Decimal, LiteralType::Decimal))
+    }
+
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
+    }
+}
+
+/// `left ^ right`. Integer powers stay integers when they are exact, i.e. for exponents which are not negative
+/// and for the bases 0, 1 and -1, other negative exponents and any decimal operand yield a decimal, e.g.
+/// `2 ^ -1` is 0.5 and `9 ^ 0.5` is 3.0. Zero to a negative power is a division by zero.
+///
+/// Decimal results are calculated with the `math` or the `std` feature, without either of them they fail with
+/// [EvalError::MissingFeature].
 struct PowerOfCommand {}
 
 impl OperatorCommand for PowerOfCommand {
-    fn eval_boolean_literals(&self, left: bool, right: bool) -> Result<Literal, &'static str> {
-        Err("<left ^ right> is not applicable for booleans!")
+    fn operator(&self) -> Operator {
+        Operator::PowerOf
+    }
+
+    fn eval_integer_overflow(&self, left: i64, right: i64) -> Option<OverflowedInteger> {
+        let exponent = u64::try_from(right).ok()?;
+        let wrapped = pow_by_squaring(left, exponent, 1, i64::wrapping_mul);
+        let exact = pow_by_squaring(left as f64, exponent, 1.0, |left, right| left * right);
+        Some(OverflowedInteger::new(wrapped, exact))
     }
 
-    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, &'static str> {
-        Ok(Literal::Integer(left.pow(right as u32)))
+    fn eval_ieee_division_by_zero(&self, left: f64, right: f64) -> Option<f64> {
+        // IEEE 754 pow of a zero base and a negative exponent, only -0 to an odd power is negative
+        let odd = right % 2.0 == -1.0;
+        Some(if left.is_sign_negative() && odd { f64::NEG_INFINITY } else { f64::INFINITY })
+    }
+
+    fn eval_boolean_literals(&self, _left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::Boolean, LiteralType::Boolean))
+    }
+
+    fn eval_integer_literals(&self, left: i64, right: i64) -> Result<Literal, EvalError> {
+        match left {
+            0 if right < 0 => Err(EvalError::DivisionByZero { span: Span::default() }),
+            0 => Ok(Literal::Integer(if right == 0 { 1 } else { 0 })),
+            1 => Ok(Literal::Integer(1)),
+            -1 => Ok(Literal::Integer(if right % 2 == 0 { 1 } else { -1 })),
+            _ if right < 0 => pow(left as f64, right as f64).map(Literal::Decimal),
+            _ => u32::try_from(right).ok()
+                .and_then(|exponent| left.checked_pow(exponent))
+                .map(Literal::Integer)
+                .ok_or_else(|| self.overflow()),
+        }
     }
 
-    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, &'static str> {
-        Err("<left ^ right> is not applicable for decimals!")
+    fn eval_decimal_literals(&self, left: f64, right: f64) -> Result<Literal, EvalError> {
+        if left == 0.0 && right < 0.0 {
+            return Err(EvalError::DivisionByZero { span: Span::default() });
+        }
+        pow(left, right).map(Literal::Decimal)
     }
 
-    fn eval_string_literals(&self, left: &str, right: &str) -> Result<Literal, &'static str> {
-        Err("<left ^ right> is not applicable for two strings!")
+    fn eval_string_literals(&self, _left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Err(self.type_mismatch(LiteralType::String, LiteralType::String))
     }
 }
 
+#[cfg(feature = "math")]
+fn pow(base: f64, exponent: f64) -> Result<f64, EvalError> {
+    Ok(libm::pow(base, exponent))
+}
+
+#[cfg(all(feature = "std", not(feature = "math")))]
+fn pow(base: f64, exponent: f64) -> Result<f64, EvalError> {
+    Ok(base.powf(exponent))
+}
+
+/// `core` has no floating point powers, they come from libm or from the standard library.
+#[cfg(not(any(feature = "math", feature = "std")))]
+fn pow(_base: f64, _exponent: f64) -> Result<f64, EvalError> {
+    Err(EvalError::MissingFeature { feature: "math", span: Span::default() })
+}
+
+/// `left ?? right` is the left side unless it is null, whatever the types of both sides are.
+struct CoalesceCommand {}
+
+impl OperatorCommand for CoalesceCommand {
+    fn operator(&self) -> Operator {
+        Operator::Coalesce
+    }
+
+    fn eval_null_operands(&self, left_side: &Literal, right_side: &Literal, _null_logic: NullLogic) -> Result<Literal, EvalError> {
+        self.execute_command(right_side, left_side)
+    }
+
+    fn execute_command(&self, right_side: &Literal, left_side: &Literal) -> Result<Literal, EvalError> {
+        match left_side {
+            Literal::Null => Ok(right_side.clone()),
+            _ => Ok(left_side.clone()),
+        }
+    }
+
+    fn eval_boolean_literals(&self, left: bool, _right: bool) -> Result<Literal, EvalError> {
+        Ok(Literal::Boolean(left))
+    }
+
+    fn eval_integer_literals(&self, left: i64, _right: i64) -> Result<Literal, EvalError> {
+        Ok(Literal::Integer(left))
+    }
+
+    fn eval_decimal_literals(&self, left: f64, _right: f64) -> Result<Literal, EvalError> {
+        Ok(Literal::Decimal(left))
+    }
+
+    fn eval_string_literals(&self, left: &str, _right: &str) -> Result<Literal, EvalError> {
+        Ok(Literal::String(Box::from(left)))
+    }
+}
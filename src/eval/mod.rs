@@ -4,39 +4,111 @@ extern crate alloc;
 
 
 
+use alloc::string::ToString;
 use alloc::vec::Vec;
-use crate::definition::{Operator, Token};
+use crate::ast::Expr;
+use crate::context::Context;
+use crate::definition::Operator;
+use crate::error::{EvalError, Span};
 use crate::eval::evaluation::OperatorExecutor;
+use crate::function::FunctionRegistry;
 use crate::Literal;
+use crate::options::{EvalOptions, MissingVariables, NullLogic};
+use crate::path::VariablePath;
 
-pub fn evaluate_tokens(tokens: Vec<Token>) -> Result<Literal, &'static str> {
-    let mut stack: Vec<Literal> = Vec::new();
-
-    tokens.into_iter().for_each(|token| {
-        match token {
-            Token::Operator(operator_properties) => {
-                let right_side = stack.pop().unwrap();
-                let left_side = stack.pop().unwrap();
-                let operator = operator_properties.operator;
-
-                let result = OperatorExecutor::new().execute(&operator, &right_side, &left_side);
-                //TODO error handling
-                if let Ok(literal) = result { stack.push(literal)}
+pub fn evaluate_ast<C: Context + ?Sized>(expression: &Expr, context: &C, functions: &FunctionRegistry, options: &EvalOptions) -> Result<Literal, EvalError> {
+    Evaluator { context, functions, options, executor: OperatorExecutor::new(options) }.evaluate(expression)
+}
 
+struct Evaluator<'a, C: Context + ?Sized> {
+    context: &'a C,
+    functions: &'a FunctionRegistry,
+    options: &'a EvalOptions,
+    executor: OperatorExecutor,
+}
 
+impl<C: Context + ?Sized> Evaluator<'_, C> {
+    fn evaluate(&self, expression: &Expr) -> Result<Literal, EvalError> {
+        match expression {
+            Expr::Literal { value, .. } => {
+                Ok(value.clone())
             }
-            Token::Literal(literal) => {
-                stack.push(literal);
+            Expr::Variable { path, span } => {
+                self.lookup(path)
+                    .or_else(|| (self.options.missing_variables == MissingVariables::Null).then_some(Literal::Null))
+                    .ok_or_else(|| EvalError::UnknownVariable { name: path.to_string().into_boxed_str(), span: *span })
             }
-
-            _ => {
-                //TODO panic if occurs
+            Expr::Unary { operator, operand, span } => {
+                let operand = self.evaluate(operand)?;
+                self.executor.execute_unary(operator, &operand, *span)
+            }
+            Expr::Binary { operator, left, right, span } => {
+                let left_side = match operator {
+                    Operator::Coalesce => self.evaluate_or_null(left)?,
+                    _ => self.evaluate(left)?,
+                };
+                if let Some(result) = short_circuit(operator, &left_side, self.options.null_logic) {
+                    return Ok(result);
+                }
+                let right_side = self.evaluate(right)?;
+                self.executor.execute(operator, &right_side, &left_side, *span)
+            }
+            Expr::Grouping { inner, .. } => {
+                self.evaluate(inner)
+            }
+            Expr::Call { name, arguments, span } => {
+                self.call(name, arguments, *span)
+            }
+            Expr::Conditional { condition, then_branch, else_branch, .. } => {
+                match self.evaluate(condition)? {
+                    Literal::Boolean(true) => self.evaluate(then_branch),
+                    Literal::Boolean(false) | Literal::Null => self.evaluate(else_branch),
+                    other => Err(EvalError::InvalidCondition { found: other.literal_type(), span: condition.span() }),
+                }
             }
         }
-    });
+    }
 
+    fn lookup(&self, path: &VariablePath) -> Option<Literal> {
+        self.context.get(path)
+            .or_else(|| path.as_identifier().and_then(|name| self.functions.constant(name)).cloned())
+    }
 
-    stack.pop().ok_or("something is wrong")
+    /// Evaluates the left side of `??`, where a missing variable is null whatever the [MissingVariables] policy is.
+    fn evaluate_or_null(&self, expression: &Expr) -> Result<Literal, EvalError> {
+        match expression {
+            Expr::Variable { path, .. } => Ok(self.lookup(path).unwrap_or(Literal::Null)),
+            Expr::Grouping { inner, .. } => self.evaluate_or_null(inner),
+            _ => self.evaluate(expression),
+        }
+    }
+
+    fn call(&self, name: &str, arguments: &[Expr], span: Span) -> Result<Literal, EvalError> {
+        let function = self.functions.resolve(name, arguments.len(), span)?;
+        let values = arguments.iter()
+            .map(|argument| self.evaluate(argument))
+            .collect::<Result<Vec<Literal>, EvalError>>()?;
+
+        function.call(&values).map_err(|error| match error {
+            EvalError::InvalidArgument { index, .. } if index < arguments.len() => {
+                error.with_span(arguments[index].span())
+            }
+            _ => error.with_span(span),
+        })
+    }
+}
+
+/// The result of `&&`, `||` and `??` if it is already decided by the left side, in which case the right side
+/// is not evaluated at all. Non boolean left sides are left to the operator command to report.
+fn short_circuit(operator: &Operator, left_side: &Literal, null_logic: NullLogic) -> Option<Literal> {
+    match (operator, left_side) {
+        (Operator::And, Literal::Boolean(false)) => Some(Literal::Boolean(false)),
+        (Operator::And, Literal::Null) if null_logic == NullLogic::NullIsFalse => Some(Literal::Boolean(false)),
+        (Operator::Or, Literal::Boolean(true)) => Some(Literal::Boolean(true)),
+        (Operator::Coalesce, Literal::Null) => None,
+        (Operator::Coalesce, _) => Some(left_side.clone()),
+        _ => None,
+    }
 }
 
 
@@ -46,17 +118,396 @@ pub fn evaluate_tokens(tokens: Vec<Token>) -> Result<Literal, &'static str> {
 mod tests {
     extern crate std;
 
-    use crate::converter::convert_infix_to_postfix_notation;
-    use crate::eval::{evaluate_tokens};
-    use crate::Literal;
-    use crate::tokenizer::{string_to_tokens};
+    use alloc::collections::btree_map::BTreeMap;
+    use crate::context::{Context, EmptyContext};
+    use crate::eval::evaluate_ast;
+    use crate::function::FunctionRegistry;
+    use rstest::rstest;
+    use crate::options::{Coercion, EvalOptions, IntegerOverflow, MissingVariables, Modulo, NullLogic, ZeroDivision};
+    #[cfg(feature = "decimal")]
+    use std::string::ToString;
+    #[cfg(feature = "decimal")]
+    use crate::options::Rounding;
+    use crate::definition::{LiteralType, Operator};
+    use crate::error::{EvalError, Span};
+    use crate::{compile, Literal, parse};
+
+
+    fn evaluate(expression: &str, options: &EvalOptions) -> Result<Literal, EvalError> {
+        evaluate_in(expression, &EmptyContext, options)
+    }
 
+    fn evaluate_in<C: Context + ?Sized>(expression: &str, context: &C, options: &EvalOptions) -> Result<Literal, EvalError> {
+        parse(expression).and_then(|expression| evaluate_ast(&expression, context, &FunctionRegistry::new(), options))
+    }
 
     #[test]
-    fn eval_tokens() {
-        let result = string_to_tokens("( 1.0 + 5 ) / 2 + 3.0 > 5 && 6 < 5").and_then(convert_infix_to_postfix_notation).and_then(evaluate_tokens);
+    fn eval_ast() {
+        let result = evaluate("( 1.0 + 5 ) / 2 + 3.0 > 5 && 6 < 5", &EvalOptions::default());
         assert_matches!(result, Result::Ok(literal) => {
             assert_eq!(literal, Literal::Boolean(false));
         });
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn eval_errors() {
+        assert_eq!(evaluate("1 + 4 / 0 ", &EvalOptions::default()), Err(EvalError::DivisionByZero { span: Span::new(4, 9) }));
+        assert_eq!(evaluate("9223372036854775807 + 1 ", &EvalOptions::default()), Err(EvalError::Overflow { operator: Operator::Plus, span: Span::new(0, 23) }));
+        assert_eq!(evaluate("true + 1 ", &EvalOptions::default()), Err(EvalError::TypeMismatch { operator: Operator::Plus, left: LiteralType::Boolean, right: LiteralType::Integer, span: Span::new(0, 8) }));
+        assert_eq!(evaluate("max(1, 2)", &EvalOptions::default()), Err(EvalError::UnknownFunction { name: "max".into(), span: Span::new(0, 9) }));
+        assert_eq!(evaluate("1 + max(missing)", &EvalOptions::default()), Err(EvalError::UnknownFunction { name: "max".into(), span: Span::new(4, 16) }));
+        assert_eq!(evaluate("a + 1 ", &EvalOptions::default()), Err(EvalError::UnknownVariable { name: "a".into(), span: Span::new(0, 1) }));
+    }
+
+    #[test]
+    fn eval_unary_operators() {
+        assert_eq!(evaluate("!true", &EvalOptions::default()), Ok(Literal::Boolean(false)));
+        assert_eq!(evaluate("!(3 > 2)", &EvalOptions::default()), Ok(Literal::Boolean(false)));
+        assert_eq!(evaluate("!!true && !false", &EvalOptions::default()), Ok(Literal::Boolean(true)));
+        assert_eq!(evaluate("-3", &EvalOptions::default()), Ok(Literal::Integer(-3)));
+        assert_eq!(evaluate("2 * -3", &EvalOptions::default()), Ok(Literal::Integer(-6)));
+        assert_eq!(evaluate("-2 ^ 2", &EvalOptions::default()), Ok(Literal::Integer(-4)));
+        assert_eq!(evaluate("(-2) ^ 2", &EvalOptions::default()), Ok(Literal::Integer(4)));
+        assert_eq!(evaluate("- -1.5", &EvalOptions::default()), Ok(Literal::Decimal(1.5)));
+        assert_eq!(evaluate("+4 - +2", &EvalOptions::default()), Ok(Literal::Integer(2)));
+        assert_eq!(evaluate("1 - -1", &EvalOptions::default()), Ok(Literal::Integer(2)));
+    }
+
+    #[test]
+    fn eval_unary_errors() {
+        assert_eq!(evaluate("1 + !3", &EvalOptions::default()), Err(EvalError::UnsupportedOperand { operator: Operator::Not, operand: LiteralType::Integer, span: Span::new(4, 6) }));
+        assert_eq!(evaluate("-true", &EvalOptions::default()), Err(EvalError::UnsupportedOperand { operator: Operator::UnaryMinus, operand: LiteralType::Boolean, span: Span::new(0, 5) }));
+        assert_eq!(evaluate("-(-9223372036854775807 - 1)", &EvalOptions::default()), Err(EvalError::Overflow { operator: Operator::UnaryMinus, span: Span::new(0, 27) }));
+    }
+
+    #[test]
+    fn short_circuit_skips_right_side() {
+        let expression = compile("x != 0 && 10 / x > 1").unwrap();
+
+        assert_eq!(expression.eval(&BTreeMap::from([("x", Literal::Integer(0))])), Ok(Literal::Boolean(false)));
+        assert_eq!(expression.eval(&BTreeMap::from([("x", Literal::Integer(5))])), Ok(Literal::Boolean(true)));
+
+        assert_eq!(evaluate("false && missing", &EvalOptions::default()), Ok(Literal::Boolean(false)));
+        assert_eq!(evaluate("true || 1 / 0 == 1", &EvalOptions::default()), Ok(Literal::Boolean(true)));
+        assert_eq!(evaluate("true && missing", &EvalOptions::default()), Err(EvalError::UnknownVariable { name: "missing".into(), span: Span::new(8, 15) }));
+        assert_eq!(evaluate("false || missing", &EvalOptions::default()), Err(EvalError::UnknownVariable { name: "missing".into(), span: Span::new(9, 16) }));
+    }
+
+    #[test]
+    fn short_circuit_keeps_type_checks() {
+        assert_eq!(evaluate("1 && false", &EvalOptions::default()), Err(EvalError::TypeMismatch { operator: Operator::And, left: LiteralType::Integer, right: LiteralType::Boolean, span: Span::new(0, 10) }));
+        assert_eq!(evaluate("1 && 1.5", &EvalOptions::default()), Err(EvalError::TypeMismatch { operator: Operator::And, left: LiteralType::Integer, right: LiteralType::Decimal, span: Span::new(0, 8) }));
+    }
+
+    #[rstest]
+    #[case::strings_strict("'a' + 'b'", Coercion::Strict, Ok(Literal::String("ab".into())))]
+    #[case::strings_lenient("'a' + 'b'", Coercion::Lenient, Ok(Literal::String("ab".into())))]
+    #[case::numbers_strict("1 + 2.5", Coercion::Strict, Ok(Literal::Decimal(3.5)))]
+    #[case::string_integer_strict("'v' + 1", Coercion::Strict, Err(EvalError::TypeMismatch { operator: Operator::Plus, left: LiteralType::String, right: LiteralType::Integer, span: Span::new(0, 7) }))]
+    #[case::string_integer_lenient("'v' + 1", Coercion::Lenient, Ok(Literal::String("v1".into())))]
+    #[case::decimal_string_lenient("1.0 + 'v'", Coercion::Lenient, Ok(Literal::String("1.0v".into())))]
+    #[case::string_boolean_lenient("'is' + true", Coercion::Lenient, Ok(Literal::String("istrue".into())))]
+    #[case::boolean_integer_lenient("true + 1", Coercion::Lenient, Err(EvalError::TypeMismatch { operator: Operator::Plus, left: LiteralType::Boolean, right: LiteralType::Integer, span: Span::new(0, 8) }))]
+    #[case::only_plus_coerces("'v' * 2", Coercion::Lenient, Err(EvalError::TypeMismatch { operator: Operator::Multiply, left: LiteralType::String, right: LiteralType::Integer, span: Span::new(0, 7) }))]
+    #[case::comparison_does_not_coerce("'one' == 1", Coercion::Lenient, Err(EvalError::TypeMismatch { operator: Operator::Equal, left: LiteralType::String, right: LiteralType::Integer, span: Span::new(0, 10) }))]
+    fn coercion_table(#[case] expression: &str, #[case] coercion: Coercion, #[case] expected: Result<Literal, EvalError>) {
+        let options = EvalOptions::default().with_coercion(coercion);
+
+        assert_eq!(evaluate(expression, &options), expected);
+    }
+
+    #[rstest]
+    #[case::missing_takes_default("threshold ?? 10", MissingVariables::Error, Ok(Literal::Integer(10)))]
+    #[case::present_keeps_value("limit ?? 10", MissingVariables::Error, Ok(Literal::Integer(3)))]
+    #[case::null_takes_default("nothing ?? 'x'", MissingVariables::Error, Ok(Literal::String("x".into())))]
+    #[case::chained("a ?? b ?? limit", MissingVariables::Error, Ok(Literal::Integer(3)))]
+    #[case::grouped("(threshold) ?? 1", MissingVariables::Error, Ok(Literal::Integer(1)))]
+    #[case::binds_tighter_than_comparison("5 > threshold ?? 1 + 2", MissingVariables::Error, Ok(Literal::Boolean(true)))]
+    #[case::right_side_is_lazy("limit ?? 1 / 0", MissingVariables::Error, Ok(Literal::Integer(3)))]
+    #[case::missing_is_an_error("threshold + 1", MissingVariables::Error, Err(EvalError::UnknownVariable { name: "threshold".into(), span: Span::new(0, 9) }))]
+    #[case::missing_in_default_is_an_error("threshold ?? other", MissingVariables::Error, Err(EvalError::UnknownVariable { name: "other".into(), span: Span::new(13, 18) }))]
+    #[case::missing_is_null("threshold", MissingVariables::Null, Ok(Literal::Null))]
+    #[case::missing_path_is_null("user.name ?? 'anonymous'", MissingVariables::Null, Ok(Literal::String("anonymous".into())))]
+    #[case::null_propagates("threshold + 1", MissingVariables::Null, Ok(Literal::Null))]
+    fn missing_variable_policy(#[case] expression: &str, #[case] missing_variables: MissingVariables, #[case] expected: Result<Literal, EvalError>) {
+        let options = EvalOptions::default().with_missing_variables(missing_variables);
+        let context = BTreeMap::from([("limit", Literal::Integer(3)), ("nothing", Literal::Null)]);
+
+        assert_eq!(evaluate_in(expression, &context, &options), expected);
+    }
+
+    #[rstest]
+    #[case::arithmetic("nothing + 1", Literal::Null, Literal::Null)]
+    #[case::unary_minus("-nothing", Literal::Null, Literal::Null)]
+    #[case::string_concatenation("'a' + nothing", Literal::Null, Literal::Null)]
+    #[case::equal_null("nothing == null", Literal::Null, Literal::Boolean(true))]
+    #[case::equal("nothing == 1", Literal::Null, Literal::Boolean(false))]
+    #[case::not_equal("nothing != 1", Literal::Null, Literal::Boolean(true))]
+    #[case::not_equal_null("null != nothing", Literal::Null, Literal::Boolean(false))]
+    #[case::less("nothing < 1", Literal::Null, Literal::Boolean(false))]
+    #[case::greater_or_equal("1 >= nothing", Literal::Null, Literal::Boolean(false))]
+    #[case::not("!nothing", Literal::Null, Literal::Boolean(true))]
+    #[case::and_true("nothing && true", Literal::Null, Literal::Boolean(false))]
+    #[case::true_and("true && nothing", Literal::Null, Literal::Boolean(false))]
+    #[case::and_false("nothing && false", Literal::Boolean(false), Literal::Boolean(false))]
+    #[case::false_and("false && nothing", Literal::Boolean(false), Literal::Boolean(false))]
+    #[case::or_true("nothing || true", Literal::Boolean(true), Literal::Boolean(true))]
+    #[case::true_or("true || nothing", Literal::Boolean(true), Literal::Boolean(true))]
+    #[case::or_false("nothing || false", Literal::Null, Literal::Boolean(false))]
+    #[case::null_and_null("nothing && null", Literal::Null, Literal::Boolean(false))]
+    #[case::is_null("nothing is null", Literal::Boolean(true), Literal::Boolean(true))]
+    #[case::is_not_null("nothing is not null", Literal::Boolean(false), Literal::Boolean(false))]
+    #[case::value_is_null("limit is null", Literal::Boolean(false), Literal::Boolean(false))]
+    #[case::value_is_not_null("limit + 1 is not null && limit > 2", Literal::Boolean(true), Literal::Boolean(true))]
+    #[case::comparison_is_null("nothing > 1 is null", Literal::Boolean(true), Literal::Boolean(false))]
+    #[case::coalesce("nothing ?? limit", Literal::Integer(3), Literal::Integer(3))]
+    fn null_logic(#[case] expression: &str, #[case] three_valued: Literal, #[case] null_is_false: Literal) {
+        let context = BTreeMap::from([("limit", Literal::Integer(3)), ("nothing", Literal::Null)]);
+        let evaluate_with = |null_logic| evaluate_in(expression, &context, &EvalOptions::default().with_null_logic(null_logic));
+
+        assert_eq!(evaluate_with(NullLogic::ThreeValued), Ok(three_valued));
+        assert_eq!(evaluate_with(NullLogic::NullIsFalse), Ok(null_is_false));
+    }
+
+    #[rstest]
+    #[case::and_integer("nothing && 1", NullLogic::ThreeValued, EvalError::TypeMismatch { operator: Operator::And, left: LiteralType::Null, right: LiteralType::Integer, span: Span::new(0, 12) })]
+    #[case::or_string("'a' || nothing", NullLogic::NullIsFalse, EvalError::TypeMismatch { operator: Operator::Or, left: LiteralType::String, right: LiteralType::Boolean, span: Span::new(0, 14) })]
+    fn null_logic_keeps_type_checks(#[case] expression: &str, #[case] null_logic: NullLogic, #[case] expected: EvalError) {
+        let context = BTreeMap::from([("nothing", Literal::Null)]);
+        let options = EvalOptions::default().with_null_logic(null_logic);
+
+        assert_eq!(evaluate_in(expression, &context, &options), Err(expected));
+    }
+
+    #[rstest]
+    #[case::then_branch("qty > 100 ? price * 0.9 : price", 200, Ok(Literal::Decimal(9.0)))]
+    #[case::else_branch("qty > 100 ? price * 0.9 : price", 50, Ok(Literal::Integer(10)))]
+    #[case::keyword_form("if qty > 100 then 'bulk' else 'single'", 200, Ok(Literal::String("bulk".into())))]
+    #[case::chain("qty > 100 ? 3 : qty > 10 ? 2 : 1", 50, Ok(Literal::Integer(2)))]
+    #[case::nested_in_operator("1 + if qty > 10 then 1 else 0", 50, Ok(Literal::Integer(2)))]
+    #[case::only_the_chosen_branch_runs("qty == 0 ? 0 : 100 / qty", 0, Ok(Literal::Integer(0)))]
+    #[case::else_branch_is_lazy("qty > 0 ? qty : missing", 5, Ok(Literal::Integer(5)))]
+    #[case::null_condition_chooses_else("nothing > 1 ? 1 : 2", 0, Ok(Literal::Integer(2)))]
+    #[case::chosen_branch_errors("qty > 0 ? missing : 1", 5, Err(EvalError::UnknownVariable { name: "missing".into(), span: Span::new(10, 17) }))]
+    #[case::condition_must_be_boolean("qty ? 1 : 2", 5, Err(EvalError::InvalidCondition { found: LiteralType::Integer, span: Span::new(0, 3) }))]
+    fn conditionals(#[case] expression: &str, #[case] qty: i64, #[case] expected: Result<Literal, EvalError>) {
+        let context = BTreeMap::from([("qty", Literal::Integer(qty)), ("price", Literal::Integer(10)), ("nothing", Literal::Null)]);
+
+        assert_eq!(evaluate_in(expression, &context, &EvalOptions::default()), expected);
+    }
+
+    #[rstest]
+    #[case::modulo("7 % 3", Literal::Integer(1), Literal::Integer(1))]
+    #[case::modulo_negative_dividend("-7 % 3", Literal::Integer(-1), Literal::Integer(2))]
+    #[case::modulo_negative_divisor("7 % -3", Literal::Integer(1), Literal::Integer(1))]
+    #[case::modulo_minimum("(-9223372036854775807 - 1) % -1", Literal::Integer(0), Literal::Integer(0))]
+    #[case::modulo_decimal("-7.5 % 2", Literal::Decimal(-1.5), Literal::Decimal(0.5))]
+    #[case::integer_divide("7 // 2", Literal::Integer(3), Literal::Integer(3))]
+    #[case::integer_divide_negative_dividend("-7 // 3", Literal::Integer(-2), Literal::Integer(-3))]
+    #[case::integer_divide_negative_divisor("7 // -3", Literal::Integer(-2), Literal::Integer(-2))]
+    #[case::division_identity("(-7 // 3) * 3 + -7 % 3", Literal::Integer(-7), Literal::Integer(-7))]
+    #[case::bit_and("12 & 10", Literal::Integer(8), Literal::Integer(8))]
+    #[case::bit_or("12 | 10", Literal::Integer(14), Literal::Integer(14))]
+    #[case::bit_xor("12 xor 10", Literal::Integer(6), Literal::Integer(6))]
+    #[case::bit_not("~0", Literal::Integer(-1), Literal::Integer(-1))]
+    #[case::shift_left("1 << 62", Literal::Integer(1 << 62), Literal::Integer(1 << 62))]
+    #[case::shift_right_keeps_sign("-16 >> 2", Literal::Integer(-4), Literal::Integer(-4))]
+    #[case::mask_then_compare("0b1011 & 0b10 == 2 && 5 % 2 == 1", Literal::Boolean(true), Literal::Boolean(true))]
+    fn integer_operators(#[case] expression: &str, #[case] truncated: Literal, #[case] euclidean: Literal) {
+        let evaluate_with = |modulo| evaluate(expression, &EvalOptions::default().with_modulo(modulo));
+
+        assert_eq!(evaluate_with(Modulo::Truncated), Ok(truncated));
+        assert_eq!(evaluate_with(Modulo::Euclidean), Ok(euclidean));
+    }
+
+    #[rstest]
+    #[case::modulo_by_zero("1 % 0", EvalError::DivisionByZero { span: Span::new(0, 5) })]
+    #[case::integer_divide_by_zero("1 // 0", EvalError::DivisionByZero { span: Span::new(0, 6) })]
+    #[case::integer_divide_overflow("(-9223372036854775807 - 1) // -1", EvalError::Overflow { operator: Operator::IntegerDivide, span: Span::new(0, 32) })]
+    #[case::integer_divide_decimals("7.5 // 2", EvalError::TypeMismatch { operator: Operator::IntegerDivide, left: LiteralType::Decimal, right: LiteralType::Integer, span: Span::new(0, 8) })]
+    #[case::integer_divide_by_decimal("7 // 2.5", EvalError::TypeMismatch { operator: Operator::IntegerDivide, left: LiteralType::Integer, right: LiteralType::Decimal, span: Span::new(0, 8) })]
+    #[case::shift_too_far("1 << 64", EvalError::Overflow { operator: Operator::ShiftLeft, span: Span::new(0, 7) })]
+    #[case::negative_shift("1 >> -1", EvalError::Overflow { operator: Operator::ShiftRight, span: Span::new(0, 7) })]
+    #[case::bit_and_booleans("true & false", EvalError::TypeMismatch { operator: Operator::BitAnd, left: LiteralType::Boolean, right: LiteralType::Boolean, span: Span::new(0, 12) })]
+    #[case::bit_and_mixed("1.5 & 1", EvalError::TypeMismatch { operator: Operator::BitAnd, left: LiteralType::Decimal, right: LiteralType::Integer, span: Span::new(0, 7) })]
+    #[case::bit_not_decimal("~1.5", EvalError::UnsupportedOperand { operator: Operator::BitNot, operand: LiteralType::Decimal, span: Span::new(0, 4) })]
+    fn integer_operator_errors(#[case] expression: &str, #[case] expected: EvalError) {
+        assert_eq!(evaluate(expression, &EvalOptions::default()), Err(expected));
+    }
+
+    fn evaluate_with_overflow(expression: &str, integer_overflow: IntegerOverflow) -> Result<Literal, EvalError> {
+        let context = BTreeMap::from([("max", Literal::Integer(i64::MAX)), ("min", Literal::Integer(i64::MIN))]);
+        let options = EvalOptions::default().with_integer_overflow(integer_overflow);
+        evaluate_in(expression, &context, &options)
+    }
+
+    #[rstest]
+    #[case::plus("max + 1", i64::MIN, i64::MAX, 9.223372036854776e18)]
+    #[case::minus("min - 1", i64::MAX, i64::MIN, -9.223372036854776e18)]
+    #[case::multiply("max * 2", -2, i64::MAX, 1.8446744073709552e19)]
+    #[case::multiply_negative("min * -1", i64::MIN, i64::MAX, 9.223372036854776e18)]
+    #[case::divide("min / -1", i64::MIN, i64::MAX, 9.223372036854776e18)]
+    #[case::integer_divide("min // -1", i64::MIN, i64::MAX, 9.223372036854776e18)]
+    #[case::power_of("2 ^ 63", i64::MIN, i64::MAX, 9.223372036854776e18)]
+    #[case::power_of_negative_base("(-2) ^ 65", 0, i64::MIN, -3.6893488147419103e19)]
+    #[case::power_of_large("10 ^ 19", -8446744073709551616, i64::MAX, 1e19)]
+    #[case::unary_minus("-min", i64::MIN, i64::MAX, 9.223372036854776e18)]
+    fn overflow_policy(#[case] expression: &str, #[case] wrapping: i64, #[case] saturating: i64, #[case] promoted: f64) {
+        assert_matches!(evaluate_with_overflow(expression, IntegerOverflow::Error), Err(EvalError::Overflow { .. }));
+        assert_eq!(evaluate_with_overflow(expression, IntegerOverflow::Wrapping), Ok(Literal::Integer(wrapping)));
+        assert_eq!(evaluate_with_overflow(expression, IntegerOverflow::Saturating), Ok(Literal::Integer(saturating)));
+        assert_eq!(evaluate_with_overflow(expression, IntegerOverflow::PromoteToDecimal), Ok(Literal::Decimal(promoted)));
+    }
+
+    #[rstest]
+    #[case::at_maximum("max - 1 + 1", Ok(Literal::Integer(i64::MAX)))]
+    #[case::at_minimum("min + 1 - 1", Ok(Literal::Integer(i64::MIN)))]
+    #[case::power_of_at_minimum("(-2) ^ 63", Ok(Literal::Integer(i64::MIN)))]
+    #[case::minimum_remainder("min % -1", Ok(Literal::Integer(0)))]
+    #[case::shift_too_far("1 << 64", Err(EvalError::Overflow { operator: Operator::ShiftLeft, span: Span::new(0, 7) }))]
+    #[case::division_by_zero("max / 0", Err(EvalError::DivisionByZero { span: Span::new(0, 7) }))]
+    fn overflow_policy_keeps_other_results(#[case] expression: &str, #[case] expected: Result<Literal, EvalError>) {
+        for integer_overflow in [IntegerOverflow::Error, IntegerOverflow::Wrapping, IntegerOverflow::Saturating, IntegerOverflow::PromoteToDecimal] {
+            assert_eq!(evaluate_with_overflow(expression, integer_overflow), expected, "{:?}", integer_overflow);
+        }
+    }
+
+    #[rstest]
+    #[case::exact_integers_stay_integers("6 / 3", Ok(Literal::Integer(2)))]
+    #[case::inexact_integers_become_decimals("7 / 2", Ok(Literal::Decimal(3.5)))]
+    #[case::negative_quotient("-7 / 2", Ok(Literal::Decimal(-3.5)))]
+    #[case::integer_division_truncates("7 // 2", Ok(Literal::Integer(3)))]
+    #[case::decimals("7.5 / 2.5", Ok(Literal::Decimal(3.0)))]
+    fn division(#[case] expression: &str, #[case] expected: Result<Literal, EvalError>) {
+        assert_eq!(evaluate(expression, &EvalOptions::default()), expected);
+    }
+
+    #[rstest]
+    #[case::integers_stay_integers("3 ^ 4", Ok(Literal::Integer(81)))]
+    #[case::zero_exponent("7 ^ 0", Ok(Literal::Integer(1)))]
+    #[case::negative_exponent_of_one("1 ^ -5", Ok(Literal::Integer(1)))]
+    #[case::negative_exponent_of_minus_one("(-1) ^ -3", Ok(Literal::Integer(-1)))]
+    #[case::huge_exponent_of_minus_one("(-1) ^ 9223372036854775807", Ok(Literal::Integer(-1)))]
+    #[case::huge_exponent_of_zero("0 ^ 9223372036854775807", Ok(Literal::Integer(0)))]
+    #[case::zero_to_negative_power("0 ^ -1", Err(EvalError::DivisionByZero { span: Span::new(0, 6) }))]
+    #[case::decimal_zero_to_negative_power("0.0 ^ -0.5", Err(EvalError::DivisionByZero { span: Span::new(0, 10) }))]
+    #[case::booleans("true ^ false", Err(EvalError::TypeMismatch { operator: Operator::PowerOf, left: LiteralType::Boolean, right: LiteralType::Boolean, span: Span::new(0, 12) }))]
+    fn power_of(#[case] expression: &str, #[case] expected: Result<Literal, EvalError>) {
+        assert_eq!(evaluate(expression, &EvalOptions::default()), expected);
+    }
+
+    #[cfg(any(feature = "math", feature = "std"))]
+    #[rstest]
+    #[case::negative_exponent("2 ^ -1", Literal::Decimal(0.5))]
+    #[case::negative_exponent_of_negative_base("(-2) ^ -2", Literal::Decimal(0.25))]
+    #[case::decimal_base("2.5 ^ 2", Literal::Decimal(6.25))]
+    #[case::square_root("9 ^ 0.5", Literal::Decimal(3.0))]
+    #[case::decimal_negative_exponent("4.0 ^ -0.5", Literal::Decimal(0.5))]
+    fn decimal_power_of(#[case] expression: &str, #[case] expected: Literal) {
+        assert_eq!(evaluate(expression, &EvalOptions::default()), Ok(expected));
+    }
+
+    #[cfg(not(any(feature = "math", feature = "std")))]
+    #[test]
+    fn decimal_power_of_needs_a_feature() {
+        assert_eq!(evaluate("2 ^ -1", &EvalOptions::default()), Err(EvalError::MissingFeature { feature: "math", span: Span::new(0, 6) }));
+        assert_eq!(evaluate("9 ^ 0.5", &EvalOptions::default()), Err(EvalError::MissingFeature { feature: "math", span: Span::new(0, 7) }));
+        assert_eq!(evaluate("3 ^ 4", &EvalOptions::default()), Ok(Literal::Integer(81)));
+    }
+
+    #[cfg(any(feature = "math", feature = "std"))]
+    #[test]
+    fn fractional_power_of_negative_base_is_nan() {
+        let result = evaluate("(-8) ^ 0.5", &EvalOptions::default());
+
+        assert_matches!(result, Ok(Literal::Decimal(value)) if value.is_nan());
+    }
+
+    #[rstest]
+    #[case::integer("1 / 0", Literal::Decimal(f64::INFINITY))]
+    #[case::negative_integer("-1 / 0", Literal::Decimal(f64::NEG_INFINITY))]
+    #[case::decimal("1.5 / 0.0", Literal::Decimal(f64::INFINITY))]
+    #[case::negative_zero("1 / -0.0", Literal::Decimal(f64::NEG_INFINITY))]
+    #[case::integer_division("-7 // 0", Literal::Decimal(f64::NEG_INFINITY))]
+    #[case::power_of("0 ^ -1", Literal::Decimal(f64::INFINITY))]
+    #[case::infinity_compares("1 / 0 > 9223372036854775807", Literal::Boolean(true))]
+    fn ieee_division_by_zero(#[case] expression: &str, #[case] expected: Literal) {
+        let options = EvalOptions::default().with_zero_division(ZeroDivision::Ieee);
+
+        assert_eq!(evaluate(expression, &options), Ok(expected));
+    }
+
+    #[rstest]
+    #[case::integer("1 / 0", Span::new(0, 5))]
+    #[case::decimal("1.5 / 0.0", Span::new(0, 9))]
+    #[case::integer_division("7 // 0", Span::new(0, 6))]
+    #[case::modulo("7 % 0", Span::new(0, 5))]
+    #[case::decimal_modulo("7.5 % 0", Span::new(0, 7))]
+    #[case::power_of("0 ^ -2", Span::new(0, 6))]
+    fn zero_division_policy(#[case] expression: &str, #[case] span: Span) {
+        let evaluate_with = |zero_division| evaluate(expression, &EvalOptions::default().with_zero_division(zero_division));
+
+        assert_eq!(evaluate_with(ZeroDivision::Error), Err(EvalError::DivisionByZero { span }));
+        assert_eq!(evaluate_with(ZeroDivision::Null), Ok(Literal::Null));
+        assert_matches!(evaluate_with(ZeroDivision::Ieee), Ok(Literal::Decimal(_)));
+    }
+
+    #[cfg(feature = "decimal")]
+    fn money(value: &str) -> Result<Literal, EvalError> {
+        Ok(Literal::Money(rust_decimal::Decimal::from_str_exact(value).unwrap()))
+    }
+
+    #[cfg(feature = "decimal")]
+    #[rstest]
+    #[case::exact_sum("0.1d + 0.2d == 0.3d", Ok(Literal::Boolean(true)))]
+    #[case::inexact_decimal_sum("0.1 + 0.2 == 0.3", Ok(Literal::Boolean(false)))]
+    #[case::product("19.99d * 3", money("59.97"))]
+    #[case::integer_operand("100 - 0.01d", money("99.99"))]
+    #[case::division("10d / 4", money("2.5"))]
+    #[case::repeating_division("1d / 3", money("0.3333333333333333333333333333"))]
+    #[case::modulo("10.5d % 3", money("1.5"))]
+    #[case::negation("-(2.50d - 3)", money("0.50"))]
+    #[case::comparison("19.99d < 20", Ok(Literal::Boolean(true)))]
+    #[case::equality_ignores_trailing_zeros("1.50d == 1.5d", Ok(Literal::Boolean(true)))]
+    #[case::null("null * 2.5d", Ok(Literal::Null))]
+    #[case::division_by_zero("1d / 0", Err(EvalError::DivisionByZero { span: Span::new(0, 6) }))]
+    #[case::overflow("79228162514264337593543950335d + 1", Err(EvalError::Overflow { operator: Operator::Plus, span: Span::new(0, 34) }))]
+    #[case::mixed_with_decimal("1.5d + 0.5", Err(EvalError::TypeMismatch { operator: Operator::Plus, left: LiteralType::Money, right: LiteralType::Decimal, span: Span::new(0, 10) }))]
+    #[case::unsupported_operator("2d ^ 2", Err(EvalError::TypeMismatch { operator: Operator::PowerOf, left: LiteralType::Money, right: LiteralType::Integer, span: Span::new(0, 6) }))]
+    #[case::unsupported_operator_on_money("1.5d // 2d", Err(EvalError::TypeMismatch { operator: Operator::IntegerDivide, left: LiteralType::Money, right: LiteralType::Money, span: Span::new(0, 10) }))]
+    #[case::unsupported_operator_on_integer("1 // 1.5d", Err(EvalError::TypeMismatch { operator: Operator::IntegerDivide, left: LiteralType::Integer, right: LiteralType::Money, span: Span::new(0, 9) }))]
+    #[case::unsupported_unary_operator("~2d", Err(EvalError::UnsupportedOperand { operator: Operator::BitNot, operand: LiteralType::Money, span: Span::new(0, 3) }))]
+    fn money_arithmetic(#[case] expression: &str, #[case] expected: Result<Literal, EvalError>) {
+        assert_eq!(evaluate(expression, &EvalOptions::default()), expected);
+    }
+
+    #[cfg(feature = "decimal")]
+    #[rstest]
+    #[case::half_even(Rounding::HalfEven, "2.34", "2.36", "-2.34")]
+    #[case::half_up(Rounding::HalfUp, "2.35", "2.36", "-2.35")]
+    #[case::half_down(Rounding::HalfDown, "2.34", "2.35", "-2.34")]
+    #[case::up(Rounding::Up, "2.35", "2.36", "-2.35")]
+    #[case::down(Rounding::Down, "2.34", "2.35", "-2.34")]
+    #[case::ceiling(Rounding::Ceiling, "2.35", "2.36", "-2.34")]
+    #[case::floor(Rounding::Floor, "2.34", "2.35", "-2.35")]
+    fn money_rounding(#[case] rounding: Rounding, #[case] first: &str, #[case] second: &str, #[case] negative: &str) {
+        let options = EvalOptions::default().with_money_scale(2).with_money_rounding(rounding);
+
+        assert_eq!(evaluate("2.345d * 1", &options), money(first));
+        assert_eq!(evaluate("2.355d * 1", &options), money(second));
+        assert_eq!(evaluate("-2.345d * 1", &options), money(negative));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn money_scale() {
+        let options = EvalOptions::default().with_money_scale(2);
+
+        assert_eq!(evaluate("10d / 3", &options), money("3.33"));
+        assert_eq!(evaluate("19.99d * 0.175d", &options), money("3.50"));
+        // only results of operators are rounded
+        assert_eq!(evaluate("1.005d", &options), money("1.005"));
+        assert_eq!(evaluate("1.005d + 1 > 2.005d", &options), Ok(Literal::Boolean(false)));
+        assert_eq!(evaluate("2.5d * 2", &options).unwrap().to_string(), "5.0d");
+    }
+}
@@ -0,0 +1,396 @@
+//!
+//! Built-in math functions, registered by [FunctionRegistry::register_math].
+//!
+//! Functions which only select or compare values (`abs`, `min`, `max`, `clamp`, `sign` and the rounding functions)
+//! keep integers as integers and money as money, all other functions return decimals and reject money. Money may be
+//! mixed with integers but not with decimals, e.g. `max(1d, 2)` is `2d`. Results outside of the domain of a function,
+//! e.g. `sqrt(-1)`, are `NaN` as defined by IEEE 754. Functions do not see the evaluation options, an integer result
+//! which does not fit into 64 bits like `abs(-9223372036854775807 - 1)` is an overflow whatever the
+//! [crate::options::IntegerOverflow] policy is.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::f64::consts::{E, PI};
+#[cfg(feature = "decimal")]
+use rust_decimal::{Decimal, RoundingStrategy};
+use crate::definition::{LiteralType, Operator};
+use crate::error::{EvalError, Span};
+use crate::function::{ArgumentCount, FunctionRegistry};
+use crate::Literal;
+
+
+impl FunctionRegistry {
+    pub fn register_math(&mut self) -> &mut Self {
+        self
+            .register_constant("pi", Literal::Decimal(PI))
+            .register_constant("e", Literal::Decimal(E))
+
+            .register("abs", ArgumentCount::exactly(1), abs)
+            .register("min", ArgumentCount::at_least(1), |arguments| select(arguments, Ordering::Less))
+            .register("max", ArgumentCount::at_least(1), |arguments| select(arguments, Ordering::Greater))
+            .register("clamp", ArgumentCount::exactly(3), clamp)
+            .register("sign", ArgumentCount::exactly(1), sign)
+
+            .register("floor", ArgumentCount::exactly(1), |arguments| round_with(arguments, Direction::Down))
+            .register("ceil", ArgumentCount::exactly(1), |arguments| round_with(arguments, Direction::Up))
+            .register("trunc", ArgumentCount::exactly(1), |arguments| round_with(arguments, Direction::TowardZero))
+            .register("round", ArgumentCount::between(1, 2), round)
+
+            .register("sqrt", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::sqrt))
+            .register("exp", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::exp))
+            .register("ln", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::log))
+            .register("log10", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::log10))
+            .register("log", ArgumentCount::between(1, 2), log)
+
+            .register("sin", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::sin))
+            .register("cos", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::cos))
+            .register("tan", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::tan))
+            .register("asin", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::asin))
+            .register("acos", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::acos))
+            .register("atan", ArgumentCount::exactly(1), |arguments| unary(arguments, libm::atan))
+            .register("hypot", ArgumentCount::exactly(2), |arguments| Ok(Literal::Decimal(libm::hypot(number(arguments, 0)?, number(arguments, 1)?))))
+    }
+}
+
+
+/// How `floor`, `ceil`, `trunc` and `round` pick a whole number.
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Down,
+    Up,
+    TowardZero,
+    /// The nearest whole number, halfway cases are rounded away from zero.
+    Nearest,
+}
+
+impl Direction {
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            Direction::Down => libm::floor(value),
+            Direction::Up => libm::ceil(value),
+            Direction::TowardZero => libm::trunc(value),
+            Direction::Nearest => libm::round(value),
+        }
+    }
+
+    #[cfg(feature = "decimal")]
+    fn strategy(self) -> RoundingStrategy {
+        match self {
+            Direction::Down => RoundingStrategy::ToNegativeInfinity,
+            Direction::Up => RoundingStrategy::ToPositiveInfinity,
+            Direction::TowardZero => RoundingStrategy::ToZero,
+            Direction::Nearest => RoundingStrategy::MidpointAwayFromZero,
+        }
+    }
+}
+
+
+fn number(arguments: &[Literal], index: usize) -> Result<f64, EvalError> {
+    match &arguments[index] {
+        Literal::Integer(value) => Ok(*value as f64),
+        Literal::Decimal(value) => Ok(*value),
+        other => Err(EvalError::invalid_argument(index, "a number", other.literal_type())),
+    }
+}
+
+fn integer(arguments: &[Literal], index: usize) -> Result<i64, EvalError> {
+    match &arguments[index] {
+        Literal::Integer(value) => Ok(*value),
+        other => Err(EvalError::invalid_argument(index, "an integer", other.literal_type())),
+    }
+}
+
+#[cfg(feature = "decimal")]
+fn money(arguments: &[Literal], index: usize) -> Result<Decimal, EvalError> {
+    match &arguments[index] {
+        Literal::Money(value) => Ok(*value),
+        Literal::Integer(value) => Ok(Decimal::from(*value)),
+        other => Err(EvalError::invalid_argument(index, "money or an integer", other.literal_type())),
+    }
+}
+
+#[cfg(feature = "decimal")]
+fn has_money(arguments: &[Literal]) -> bool {
+    arguments.iter().any(|argument| matches!(argument, Literal::Money(_)))
+}
+
+fn unary(arguments: &[Literal], function: fn(f64) -> f64) -> Result<Literal, EvalError> {
+    Ok(Literal::Decimal(function(number(arguments, 0)?)))
+}
+
+fn abs(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    match &arguments[0] {
+        Literal::Integer(value) => value.checked_abs()
+            .map(Literal::Integer)
+            // the absolute value of i64::MIN is its negation, which overflows
+            .ok_or(EvalError::Overflow { operator: Operator::UnaryMinus, span: Span::default() }),
+        #[cfg(feature = "decimal")]
+        Literal::Money(value) => Ok(Literal::Money(value.abs())),
+        _ => Ok(Literal::Decimal(libm::fabs(number(arguments, 0)?))),
+    }
+}
+
+/// Picks the argument which compares as `wanted` against all others. Integers stay integers unless decimals are mixed
+/// in, money mixed with integers is money.
+fn select(arguments: &[Literal], wanted: Ordering) -> Result<Literal, EvalError> {
+    #[cfg(feature = "decimal")]
+    if has_money(arguments) {
+        let amounts = (0..arguments.len())
+            .map(|index| money(arguments, index))
+            .collect::<Result<Vec<Decimal>, EvalError>>()?;
+        return Ok(Literal::Money(amounts[selected(&amounts, wanted)]));
+    }
+
+    let numbers = (0..arguments.len())
+        .map(|index| number(arguments, index))
+        .collect::<Result<Vec<f64>, EvalError>>()?;
+    let selected = selected(&numbers, wanted);
+
+    if arguments.iter().all(|argument| matches!(argument, Literal::Integer(_))) {
+        Ok(arguments[selected].clone())
+    } else {
+        Ok(Literal::Decimal(numbers[selected]))
+    }
+}
+
+/// Index of the first value which compares as `wanted` against all others.
+fn selected<T: PartialOrd>(values: &[T], wanted: Ordering) -> usize {
+    let mut selected = 0;
+    for (index, candidate) in values.iter().enumerate().skip(1) {
+        if candidate.partial_cmp(&values[selected]) == Some(wanted) {
+            selected = index;
+        }
+    }
+    selected
+}
+
+/// `clamp(value, lower, upper)`, the lower bound wins if the bounds are swapped.
+fn clamp(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    #[cfg(feature = "decimal")]
+    if has_money(arguments) {
+        let (value, lower, upper) = (money(arguments, 0)?, money(arguments, 1)?, money(arguments, 2)?);
+        return Ok(Literal::Money(value.min(upper).max(lower)));
+    }
+    if let [Literal::Integer(value), Literal::Integer(lower), Literal::Integer(upper)] = arguments {
+        return Ok(Literal::Integer((*value).min(*upper).max(*lower)));
+    }
+    let (value, lower, upper) = (number(arguments, 0)?, number(arguments, 1)?, number(arguments, 2)?);
+    Ok(Literal::Decimal(libm::fmax(libm::fmin(value, upper), lower)))
+}
+
+fn sign(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    match &arguments[0] {
+        Literal::Integer(value) => Ok(Literal::Integer(value.signum())),
+        #[cfg(feature = "decimal")]
+        Literal::Money(value) => Ok(Literal::Money(match value.cmp(&Decimal::ZERO) {
+            Ordering::Less => Decimal::NEGATIVE_ONE,
+            Ordering::Equal => Decimal::ZERO,
+            Ordering::Greater => Decimal::ONE,
+        })),
+        _ => {
+            let value = number(arguments, 0)?;
+            let sign = if value > 0.0 { 1.0 } else if value < 0.0 { -1.0 } else { value };
+            Ok(Literal::Decimal(sign))
+        }
+    }
+}
+
+fn round_with(arguments: &[Literal], direction: Direction) -> Result<Literal, EvalError> {
+    match &arguments[0] {
+        Literal::Integer(value) => Ok(Literal::Integer(*value)),
+        #[cfg(feature = "decimal")]
+        Literal::Money(value) => Ok(Literal::Money(value.round_dp_with_strategy(0, direction.strategy()))),
+        _ => Ok(Literal::Decimal(direction.apply(number(arguments, 0)?))),
+    }
+}
+
+/// `round(value)` rounds half away from zero, `round(value, digits)` keeps the given number of fraction digits.
+/// Money can only be rounded to a non-negative number of digits.
+fn round(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    if arguments.len() == 1 {
+        return round_with(arguments, Direction::Nearest);
+    }
+
+    let digits = integer(arguments, 1)?;
+    #[cfg(feature = "decimal")]
+    if let Literal::Money(value) = arguments[0] {
+        let digits = u32::try_from(digits)
+            .map_err(|_| EvalError::invalid_argument(1, "a non-negative number of digits", LiteralType::Integer))?;
+        return Ok(Literal::Money(value.round_dp_with_strategy(digits, Direction::Nearest.strategy())));
+    }
+    if let Literal::Integer(value) = arguments[0] {
+        return round_integer(value, digits);
+    }
+    let value = number(arguments, 0)?;
+    let digits = i32::try_from(digits)
+        .map_err(|_| EvalError::invalid_argument(1, "a number of digits", LiteralType::Integer))?;
+    // beyond the exponents of a decimal the factor is infinite or zero and would turn the result into NaN, no decimal
+    // has fraction digits that far to the right and every finite one is rounded to zero that far to the left
+    if digits > f64::MAX_10_EXP {
+        return Ok(Literal::Decimal(value));
+    }
+    if digits < -f64::MAX_10_EXP {
+        return Ok(Literal::Decimal(if value.is_finite() { 0.0 } else { value }));
+    }
+    let factor = libm::pow(10.0, digits as f64);
+    let scaled = value * factor;
+    Ok(Literal::Decimal(if scaled.is_finite() { libm::round(scaled) / factor } else { value }))
+}
+
+/// Rounds an integer half away from zero to a multiple of `10^-digits` without going through a decimal, which could
+/// not hold every integer exactly.
+fn round_integer(value: i64, digits: i64) -> Result<Literal, EvalError> {
+    if digits >= 0 {
+        return Ok(Literal::Integer(value));
+    }
+    let factor = match u32::try_from(digits.unsigned_abs()).ok().and_then(|exponent| 10_i64.checked_pow(exponent)) {
+        Some(factor) => factor,
+        // every integer is less than half of a power of ten which does not fit into 64 bits
+        None => return Ok(Literal::Integer(0)),
+    };
+
+    let remainder = value % factor;
+    let truncated = value - remainder;
+    if remainder.abs() * 2 < factor {
+        return Ok(Literal::Integer(truncated));
+    }
+    truncated.checked_add(factor * remainder.signum())
+        .map(Literal::Integer)
+        .ok_or(EvalError::Overflow { operator: Operator::Plus, span: Span::default() })
+}
+
+/// `log(value)` is the natural logarithm, `log(value, base)` the logarithm to the given base.
+fn log(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    let value = libm::log(number(arguments, 0)?);
+    match arguments.len() {
+        1 => Ok(Literal::Decimal(value)),
+        _ => Ok(Literal::Decimal(value / libm::log(number(arguments, 1)?))),
+    }
+}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use core::f64::consts::{E, PI};
+    use rstest::rstest;
+    use crate::definition::LiteralType;
+    use crate::error::{EvalError, Span};
+    use crate::{evaluate_expression, evaluate_expression_with_options};
+    use crate::definition::Operator;
+    use crate::Literal;
+    use crate::options::{EvalOptions, IntegerOverflow};
+    use crate::Literal::{Decimal, Integer};
+
+
+    #[rstest]
+    #[case::abs_integer("abs(-3)", Integer(3))]
+    #[case::abs_decimal("abs(-3.5)", Decimal(3.5))]
+    #[case::min_integers("min(4, 2, 3)", Integer(2))]
+    #[case::min_mixed("min(4, 2.5)", Decimal(2.5))]
+    #[case::max_integers("max(4, -2, 9)", Integer(9))]
+    #[case::max_mixed("max(4, 2.5)", Decimal(4.0))]
+    #[case::clamp_integer("clamp(15, 0, 10)", Integer(10))]
+    #[case::clamp_decimal("clamp(-0.5, 0, 1)", Decimal(0.0))]
+    #[case::sign_integer("sign(-7)", Integer(-1))]
+    #[case::sign_decimal("sign(0.1)", Decimal(1.0))]
+    #[case::floor("floor(2.7)", Decimal(2.0))]
+    #[case::floor_negative("floor(-2.1)", Decimal(-3.0))]
+    #[case::ceil("ceil(2.1)", Decimal(3.0))]
+    #[case::trunc("trunc(-2.7)", Decimal(-2.0))]
+    #[case::round("round(2.5)", Decimal(3.0))]
+    #[case::round_integer("round(7)", Integer(7))]
+    #[case::round_digits("round(12.3456, 2)", Decimal(12.35))]
+    #[case::round_negative_digits("round(1234, -2)", Integer(1200))]
+    #[case::round_to_more_digits_than_decimals_have("round(1.5, 400)", Decimal(1.5))]
+    #[case::round_large_decimal_to_many_digits("round(1e300, 100)", Decimal(1e300))]
+    #[case::round_to_fewer_digits_than_decimals_have("round(1.5, -400)", Decimal(0.0))]
+    #[case::round_negative_digits_half("round(-1250, -2)", Integer(-1300))]
+    #[case::round_large_integer("round(123456789012345678, -2)", Integer(123456789012345700))]
+    #[case::round_largest_integer_down("round(9223372036854775807, -2)", Integer(9223372036854775800))]
+    #[case::round_smallest_integer("round(-9223372036854775807 - 1, -2)", Integer(-9223372036854775800))]
+    #[case::round_to_more_digits_than_integers_have("round(-9223372036854775807 - 1, -19)", Integer(0))]
+    #[case::round_to_most_negative_digits("round(5, -9223372036854775807 - 1)", Integer(0))]
+    #[case::sqrt("sqrt(16)", Decimal(4.0))]
+    #[case::exp("exp(0)", Decimal(1.0))]
+    #[case::ln("ln(1)", Decimal(0.0))]
+    #[case::log10("log10(1000)", Decimal(3.0))]
+    #[case::log_base("log(8, 2)", Decimal(3.0))]
+    #[case::sin("sin(0)", Decimal(0.0))]
+    #[case::cos("cos(0)", Decimal(1.0))]
+    #[case::atan("atan(0)", Decimal(0.0))]
+    #[case::hypot("hypot(3, 4)", Decimal(5.0))]
+    #[case::nested("max(abs(-2), sqrt(9)) * 2", Decimal(6.0))]
+    fn math_functions(#[case] expression: &str, #[case] expected: Literal) {
+        assert_eq!(evaluate_expression(expression), Ok(expected));
+    }
+
+    #[test]
+    fn math_constants() {
+        assert_eq!(evaluate_expression("pi"), Ok(Decimal(PI)));
+        assert_eq!(evaluate_expression("e"), Ok(Decimal(E)));
+        assert_eq!(evaluate_expression("cos(pi)"), Ok(Decimal(-1.0)));
+    }
+
+    #[test]
+    fn inverse_functions() {
+        assert_matches!(evaluate_expression("asin(1) * 2"), Ok(Decimal(value)) => assert!((value - PI).abs() < 1e-12));
+        assert_matches!(evaluate_expression("acos(0) * 2"), Ok(Decimal(value)) => assert!((value - PI).abs() < 1e-12));
+        assert_matches!(evaluate_expression("tan(atan(0.5))"), Ok(Decimal(value)) => assert!((value - 0.5).abs() < 1e-12));
+        assert_matches!(evaluate_expression("sqrt(-1)"), Ok(Decimal(value)) => assert!(value.is_nan()));
+    }
+
+    #[test]
+    fn math_argument_errors() {
+        assert_eq!(evaluate_expression("sqrt('four')"), Err(EvalError::InvalidArgument { index: 0, expected: "a number", found: LiteralType::String, span: Span::new(5, 11) }));
+        assert_eq!(evaluate_expression("round(1.5, 0.5)"), Err(EvalError::InvalidArgument { index: 1, expected: "an integer", found: LiteralType::Decimal, span: Span::new(11, 14) }));
+        assert_matches!(evaluate_expression("hypot(1)"), Err(EvalError::WrongArgumentCount { found: 1, .. }));
+    }
+
+    #[rstest]
+    #[case::error(IntegerOverflow::Error)]
+    #[case::promote_to_decimal(IntegerOverflow::PromoteToDecimal)]
+    fn abs_overflows(#[case] integer_overflow: IntegerOverflow) {
+        let options = EvalOptions::default().with_integer_overflow(integer_overflow);
+
+        assert_eq!(evaluate_expression_with_options("abs(-9223372036854775807 - 1)", &options), Err(EvalError::Overflow { operator: Operator::UnaryMinus, span: Span::new(0, 29) }));
+        assert_eq!(evaluate_expression_with_options("abs(-9223372036854775807)", &options), Ok(Integer(i64::MAX)));
+    }
+
+    #[test]
+    fn round_overflows() {
+        assert_eq!(evaluate_expression("round(9223372036854775807, -1)"), Err(EvalError::Overflow { operator: Operator::Plus, span: Span::new(0, 30) }));
+        assert_eq!(evaluate_expression("round(-9223372036854775807 - 1, -1)"), Err(EvalError::Overflow { operator: Operator::Plus, span: Span::new(0, 35) }));
+    }
+
+    #[cfg(feature = "decimal")]
+    fn money(value: &str) -> Literal {
+        Literal::Money(rust_decimal::Decimal::from_str_exact(value).unwrap())
+    }
+
+    #[cfg(feature = "decimal")]
+    #[rstest]
+    #[case::abs("abs(-1.25d)", money("1.25"))]
+    #[case::min("min(3d, 1.5d, 2)", money("1.5"))]
+    #[case::max_with_integer("max(1d, 2)", money("2"))]
+    #[case::clamp("clamp(12.5d, 0, 10.25d)", money("10.25"))]
+    #[case::sign("sign(-0.01d)", money("-1"))]
+    #[case::floor("floor(-2.1d)", money("-3"))]
+    #[case::ceil("ceil(2.1d)", money("3"))]
+    #[case::trunc("trunc(-2.7d)", money("-2"))]
+    #[case::round("round(2.5d)", money("3"))]
+    #[case::round_digits("round(2.345d, 2)", money("2.35"))]
+    #[case::round_negative_amount("round(-2.345d, 2)", money("-2.35"))]
+    fn money_functions(#[case] expression: &str, #[case] expected: Literal) {
+        assert_eq!(evaluate_expression(expression), Ok(expected));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn money_argument_errors() {
+        assert_eq!(evaluate_expression("sqrt(4d)"), Err(EvalError::InvalidArgument { index: 0, expected: "a number", found: LiteralType::Money, span: Span::new(5, 7) }));
+        assert_eq!(evaluate_expression("max(1d, 2.5)"), Err(EvalError::InvalidArgument { index: 1, expected: "money or an integer", found: LiteralType::Decimal, span: Span::new(8, 11) }));
+        assert_eq!(evaluate_expression("round(1250d, -2)"), Err(EvalError::InvalidArgument { index: 1, expected: "a non-negative number of digits", found: LiteralType::Integer, span: Span::new(13, 15) }));
+    }
+}
@@ -0,0 +1,280 @@
+//!
+//! Functions which can be called from an expression, e.g. `max(a, b) > 3`.
+//!
+//! ```
+//! use expression_eval_rs::compile_with_functions;
+//! use expression_eval_rs::context::EmptyContext;
+//! use expression_eval_rs::definition::Literal;
+//! use expression_eval_rs::error::EvalError;
+//! use expression_eval_rs::function::{ArgumentCount, FunctionRegistry};
+//!
+//! let mut functions = FunctionRegistry::new();
+//! functions.register("twice", ArgumentCount::exactly(1), |arguments| match &arguments[0] {
+//!     Literal::Integer(value) => Ok(Literal::Integer(value * 2)),
+//!     other => Err(EvalError::invalid_argument(0, "integer", other.literal_type())),
+//! });
+//!
+//! let expression = compile_with_functions("twice(21)", &functions).unwrap();
+//! assert_eq!(expression.eval(&EmptyContext), Ok(Literal::Integer(42)));
+//! ```
+
+#[cfg(feature = "math")]
+mod math;
+mod number;
+mod string;
+
+use alloc::boxed::Box;
+use alloc::collections::btree_map::BTreeMap;
+use alloc::sync::Arc;
+use core::fmt::{Debug, Display, Formatter};
+use crate::ast::Expr;
+use crate::error::{EvalError, Span};
+use crate::Literal;
+
+
+pub type FunctionImplementation = dyn Fn(&[Literal]) -> Result<Literal, EvalError> + Send + Sync;
+
+
+/// Number of arguments a function accepts.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct ArgumentCount {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl ArgumentCount {
+    pub const fn exactly(count: usize) -> Self {
+        Self { min: count, max: Some(count) }
+    }
+
+    pub const fn at_least(count: usize) -> Self {
+        Self { min: count, max: None }
+    }
+
+    pub const fn between(min: usize, max: usize) -> Self {
+        Self { min, max: Some(max) }
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+impl Display for ArgumentCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
+}
+
+
+#[derive(Clone)]
+pub struct Function {
+    arguments: ArgumentCount,
+    implementation: Arc<FunctionImplementation>,
+}
+
+impl Function {
+    pub fn arguments(&self) -> ArgumentCount {
+        self.arguments
+    }
+
+    pub fn call(&self, arguments: &[Literal]) -> Result<Literal, EvalError> {
+        (self.implementation)(arguments)
+    }
+}
+
+impl Debug for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Function").field("arguments", &self.arguments).finish_non_exhaustive()
+    }
+}
+
+
+/// The functions available to an expression, looked up by name, together with named constants like `pi`.
+///
+/// Cloning a registry is cheap, the functions and constants are shared until one of the clones registers more.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionRegistry {
+    functions: Arc<BTreeMap<Box<str>, Function>>,
+    constants: Arc<BTreeMap<Box<str>, Literal>>,
+}
+
+impl FunctionRegistry {
+    /// An empty registry without any built-in functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with all built-in functions enabled by the crate features. With the `std` feature it is built once
+    /// and shared by every call, otherwise each call builds a new one.
+    pub fn with_builtins() -> Self {
+        #[cfg(feature = "std")]
+        {
+            static BUILTINS: std::sync::OnceLock<FunctionRegistry> = std::sync::OnceLock::new();
+            BUILTINS.get_or_init(Self::build_builtins).clone()
+        }
+        #[cfg(not(feature = "std"))]
+        Self::build_builtins()
+    }
+
+    fn build_builtins() -> Self {
+        let mut functions = Self::new();
+        functions.register_strings();
+        functions.register_number_checks();
+        #[cfg(feature = "math")]
+        functions.register_math();
+        functions
+    }
+
+    /// Registers a constant which is used for variables the context does not provide a value for.
+    pub fn register_constant(&mut self, name: &str, value: Literal) -> &mut Self {
+        Arc::make_mut(&mut self.constants).insert(Box::from(name), value);
+        self
+    }
+
+    pub fn constant(&self, name: &str) -> Option<&Literal> {
+        self.constants.get(name)
+    }
+
+    /// Registers a function, replacing any function previously registered under the same name.
+    pub fn register<F>(&mut self, name: &str, arguments: ArgumentCount, implementation: F) -> &mut Self
+        where F: Fn(&[Literal]) -> Result<Literal, EvalError> + Send + Sync + 'static
+    {
+        let function = Function { arguments, implementation: Arc::new(implementation) };
+        Arc::make_mut(&mut self.functions).insert(Box::from(name), function);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+
+    /// Checks that every function called in the expression is registered and called with a supported number of arguments.
+    pub fn validate(&self, expression: &Expr) -> Result<(), EvalError> {
+        match expression {
+            Expr::Literal { .. } | Expr::Variable { .. } => Ok(()),
+            Expr::Unary { operand, .. } => self.validate(operand),
+            Expr::Binary { left, right, .. } => {
+                self.validate(left)?;
+                self.validate(right)
+            }
+            Expr::Grouping { inner, .. } => self.validate(inner),
+            Expr::Call { name, arguments, span } => {
+                self.resolve(name, arguments.len(), *span)?;
+                arguments.iter().try_for_each(|argument| self.validate(argument))
+            }
+            Expr::Conditional { condition, then_branch, else_branch, .. } => {
+                self.validate(condition)?;
+                self.validate(then_branch)?;
+                self.validate(else_branch)
+            }
+        }
+    }
+
+    pub(crate) fn resolve(&self, name: &str, argument_count: usize, span: Span) -> Result<&Function, EvalError> {
+        let function = self.get(name)
+            .ok_or_else(|| EvalError::UnknownFunction { name: Box::from(name), span })?;
+
+        if function.arguments.accepts(argument_count) {
+            Ok(function)
+        } else {
+            Err(EvalError::WrongArgumentCount { name: Box::from(name), expected: function.arguments, found: argument_count, span })
+        }
+    }
+}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use crate::{compile_with_functions, parse};
+    use crate::context::EmptyContext;
+    use crate::definition::LiteralType;
+    use crate::error::{EvalError, Span};
+    use crate::function::{ArgumentCount, FunctionRegistry};
+    use crate::Literal;
+    use crate::Literal::{Boolean, Integer};
+
+
+    fn registry() -> FunctionRegistry {
+        let mut functions = FunctionRegistry::new();
+        functions
+            .register("max", ArgumentCount::at_least(1), |arguments| {
+                let mut maximum = i64::MIN;
+                for (index, argument) in arguments.iter().enumerate() {
+                    match argument {
+                        Integer(value) => maximum = maximum.max(*value),
+                        other => return Err(EvalError::invalid_argument(index, "integer", other.literal_type())),
+                    }
+                }
+                Ok(Integer(maximum))
+            })
+            .register("answer", ArgumentCount::exactly(0), |_| Ok(Integer(42)));
+        functions
+    }
+
+    #[rstest]
+    #[case::exactly(ArgumentCount::exactly(2), 2, true)]
+    #[case::exactly_too_many(ArgumentCount::exactly(2), 3, false)]
+    #[case::at_least(ArgumentCount::at_least(1), 10, true)]
+    #[case::at_least_too_few(ArgumentCount::at_least(1), 0, false)]
+    #[case::between(ArgumentCount::between(1, 2), 2, true)]
+    #[case::between_too_many(ArgumentCount::between(1, 2), 3, false)]
+    fn argument_count(#[case] arguments: ArgumentCount, #[case] count: usize, #[case] expected: bool) {
+        assert_eq!(arguments.accepts(count), expected);
+    }
+
+    #[test]
+    fn call_registered_functions() {
+        let expression = compile_with_functions("max(1, answer(), 7 * 2) > 3", &registry()).unwrap();
+
+        assert_eq!(expression.eval(&EmptyContext), Ok(Boolean(true)));
+    }
+
+    #[test]
+    fn validate_at_compile_time() {
+        assert_eq!(compile_with_functions("1 + min(1, 2)", &registry()).err(), Some(EvalError::UnknownFunction { name: "min".into(), span: Span::new(4, 13) }));
+        assert_eq!(compile_with_functions("answer(1)", &registry()).err(), Some(EvalError::WrongArgumentCount { name: "answer".into(), expected: ArgumentCount::exactly(0), found: 1, span: Span::new(0, 9) }));
+        assert_eq!(compile_with_functions("max()", &registry()).err(), Some(EvalError::WrongArgumentCount { name: "max".into(), expected: ArgumentCount::at_least(1), found: 0, span: Span::new(0, 5) }));
+    }
+
+    #[test]
+    fn invalid_argument_points_to_the_argument() {
+        let expression = compile_with_functions("max(1, true)", &registry()).unwrap();
+
+        assert_eq!(expression.eval(&EmptyContext), Err(EvalError::InvalidArgument { index: 1, expected: "integer", found: LiteralType::Boolean, span: Span::new(7, 11) }));
+    }
+
+    #[test]
+    fn validate_nested_calls() {
+        let expression = parse("max(answer(2))").unwrap();
+
+        assert_matches!(registry().validate(&expression), Err(EvalError::WrongArgumentCount { found: 1, .. }));
+    }
+
+    #[test]
+    fn function_errors_point_to_the_call() {
+        let mut functions = registry();
+        functions.register("fail", ArgumentCount::exactly(0), |_| Err(EvalError::DivisionByZero { span: Span::default() }));
+        let expression = compile_with_functions("1 + fail()", &functions).unwrap();
+
+        assert_eq!(expression.eval(&EmptyContext), Err::<Literal, _>(EvalError::DivisionByZero { span: Span::new(4, 10) }));
+    }
+
+    #[test]
+    fn clones_register_independently() {
+        let builtins = FunctionRegistry::with_builtins();
+        let mut extended = FunctionRegistry::with_builtins();
+        extended.register("answer", ArgumentCount::exactly(0), |_| Ok(Integer(42))).register_constant("limit", Integer(3));
+
+        assert!(extended.get("answer").is_some() && extended.get("len").is_some());
+        assert!(builtins.get("answer").is_none() && builtins.constant("limit").is_none());
+        assert!(FunctionRegistry::with_builtins().get("answer").is_none());
+    }
+}
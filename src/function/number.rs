@@ -0,0 +1,76 @@
+//!
+//! Built-in checks for decimals which are not ordinary numbers, registered by [FunctionRegistry::register_number_checks].
+//!
+//! Such decimals are the result of dividing by zero with [crate::options::ZeroDivision::Ieee], or of math functions
+//! outside of their domain like `sqrt(-1)`. Integers and money are always finite.
+
+use crate::error::EvalError;
+use crate::function::{ArgumentCount, FunctionRegistry};
+use crate::Literal;
+
+
+impl FunctionRegistry {
+    pub fn register_number_checks(&mut self) -> &mut Self {
+        self
+            .register("is_nan", ArgumentCount::exactly(1), |arguments| check(arguments, f64::is_nan, false))
+            .register("is_finite", ArgumentCount::exactly(1), |arguments| check(arguments, f64::is_finite, true))
+    }
+}
+
+
+/// Applies `decimal_check` to a decimal, for integers and money the result is `finite_result`.
+fn check(arguments: &[Literal], decimal_check: fn(f64) -> bool, finite_result: bool) -> Result<Literal, EvalError> {
+    match &arguments[0] {
+        Literal::Decimal(value) => Ok(Literal::Boolean(decimal_check(*value))),
+        Literal::Integer(_) => Ok(Literal::Boolean(finite_result)),
+        #[cfg(feature = "decimal")]
+        Literal::Money(_) => Ok(Literal::Boolean(finite_result)),
+        other => Err(EvalError::invalid_argument(0, "a number", other.literal_type())),
+    }
+}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    #[cfg(feature = "decimal")]
+    use crate::compile;
+    use crate::compile_with_options;
+    use crate::context::EmptyContext;
+    use crate::definition::LiteralType;
+    use crate::error::{EvalError, Span};
+    use crate::Literal::Boolean;
+    use crate::options::{EvalOptions, ZeroDivision};
+
+
+    #[rstest]
+    #[case::number_is_not_nan("is_nan(1.5)", false)]
+    #[case::integer_is_finite("is_finite(-9223372036854775807)", true)]
+    #[case::zero_by_zero_is_nan("is_nan(0 / 0)", true)]
+    #[case::remainder_by_zero_is_nan("is_nan(1.5 % 0)", true)]
+    #[case::infinity_is_not_nan("is_nan(1 / 0)", false)]
+    #[case::infinity_is_not_finite("is_finite(-1 / 0.0)", false)]
+    #[case::nan_is_not_finite("is_finite(0.0 / 0)", false)]
+    #[case::nan_is_not_equal_to_itself("0 / 0 == 0 / 0", false)]
+    fn number_checks(#[case] expression: &str, #[case] expected: bool) {
+        let options = EvalOptions::default().with_zero_division(ZeroDivision::Ieee);
+
+        assert_eq!(compile_with_options(expression, &options).and_then(|expression| expression.eval(&EmptyContext)), Ok(Boolean(expected)));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn money_is_finite() {
+        assert_eq!(compile("is_nan(-1.5d)").and_then(|expression| expression.eval(&EmptyContext)), Ok(Boolean(false)));
+        assert_eq!(compile("is_finite(79228162514264337593543950335d)").and_then(|expression| expression.eval(&EmptyContext)), Ok(Boolean(true)));
+    }
+
+    #[test]
+    fn number_checks_take_numbers() {
+        let result = compile_with_options("is_nan('NaN')", &EvalOptions::default()).and_then(|expression| expression.eval(&EmptyContext));
+
+        assert_eq!(result, Err(EvalError::InvalidArgument { index: 0, expected: "a number", found: LiteralType::String, span: Span::new(7, 12) }));
+    }
+}
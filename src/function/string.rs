@@ -0,0 +1,243 @@
+//!
+//! Built-in string functions, registered by [FunctionRegistry::register_strings].
+//!
+//! Lengths, positions and widths count Unicode scalar values, not bytes, so `len('straße')` is 6.
+//! Positions start at 0 and `index_of` returns -1 if the text is not found.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::definition::LiteralType;
+use crate::error::EvalError;
+use crate::function::{ArgumentCount, FunctionRegistry};
+use crate::Literal;
+
+
+/// Longest string in characters built by `replace`, `concat`, `pad_left` and `pad_right`, so an expression can not
+/// exhaust the memory of its host.
+const MAX_LENGTH: usize = 1 << 20;
+
+
+impl FunctionRegistry {
+    pub fn register_strings(&mut self) -> &mut Self {
+        self
+            .register("len", ArgumentCount::exactly(1), |arguments| count_to_literal(string(arguments, 0)?.chars().count()))
+            .register("upper", ArgumentCount::exactly(1), |arguments| Ok(Literal::String(Box::from(string(arguments, 0)?.to_uppercase()))))
+            .register("lower", ArgumentCount::exactly(1), |arguments| Ok(Literal::String(Box::from(string(arguments, 0)?.to_lowercase()))))
+            .register("trim", ArgumentCount::exactly(1), |arguments| Ok(Literal::String(Box::from(string(arguments, 0)?.trim()))))
+
+            .register("contains", ArgumentCount::exactly(2), |arguments| Ok(Literal::Boolean(string(arguments, 0)?.contains(string(arguments, 1)?))))
+            .register("starts_with", ArgumentCount::exactly(2), |arguments| Ok(Literal::Boolean(string(arguments, 0)?.starts_with(string(arguments, 1)?))))
+            .register("ends_with", ArgumentCount::exactly(2), |arguments| Ok(Literal::Boolean(string(arguments, 0)?.ends_with(string(arguments, 1)?))))
+            .register("index_of", ArgumentCount::exactly(2), index_of)
+
+            .register("substr", ArgumentCount::between(2, 3), substr)
+            .register("replace", ArgumentCount::exactly(3), replace)
+            .register("split", ArgumentCount::exactly(2), split)
+            .register("concat", ArgumentCount::at_least(1), concat)
+
+            .register("pad_left", ArgumentCount::between(2, 3), |arguments| pad(arguments, true))
+            .register("pad_right", ArgumentCount::between(2, 3), |arguments| pad(arguments, false))
+    }
+}
+
+
+fn string(arguments: &[Literal], index: usize) -> Result<&str, EvalError> {
+    match &arguments[index] {
+        Literal::String(value) => Ok(value),
+        other => Err(EvalError::invalid_argument(index, "a string", other.literal_type())),
+    }
+}
+
+fn count(arguments: &[Literal], index: usize) -> Result<usize, EvalError> {
+    match &arguments[index] {
+        Literal::Integer(value) => usize::try_from(*value)
+            .map_err(|_| EvalError::invalid_argument(index, "a non-negative integer", LiteralType::Integer)),
+        other => Err(EvalError::invalid_argument(index, "a non-negative integer", other.literal_type())),
+    }
+}
+
+fn count_to_literal(count: usize) -> Result<Literal, EvalError> {
+    // a string can not hold more than i64::MAX characters on any supported platform
+    Ok(Literal::Integer(count as i64))
+}
+
+fn index_of(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    let (text, needle) = (string(arguments, 0)?, string(arguments, 1)?);
+    match text.find(needle) {
+        Some(byte_index) => count_to_literal(text[..byte_index].chars().count()),
+        None => Ok(Literal::Integer(-1)),
+    }
+}
+
+/// `substr(text, start)` or `substr(text, start, length)`, positions past the end of the text yield an empty string.
+fn substr(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    let text = string(arguments, 0)?;
+    let start = count(arguments, 1)?;
+    let length = match arguments.len() {
+        2 => usize::MAX,
+        _ => count(arguments, 2)?,
+    };
+    Ok(Literal::String(text.chars().skip(start).take(length).collect::<String>().into_boxed_str()))
+}
+
+fn split(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    let (text, separator) = (string(arguments, 0)?, string(arguments, 1)?);
+    if separator.is_empty() {
+        return Err(EvalError::invalid_argument(1, "a non-empty separator", LiteralType::String));
+    }
+    let parts = text.split(separator)
+        .map(|part| Literal::String(Box::from(part)))
+        .collect::<Vec<Literal>>();
+    Ok(Literal::List(parts))
+}
+
+/// `replace(text, from, to)` replaces every occurrence of `from`, the result is at most [MAX_LENGTH] characters long.
+fn replace(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    let (text, from, to) = (string(arguments, 0)?, string(arguments, 1)?, string(arguments, 2)?);
+    let too_long = || EvalError::invalid_argument(2, "a replacement with a result of at most 1048576 characters", LiteralType::String);
+    let occurrences = text.matches(from).count();
+    let length = (text.chars().count() + occurrences * to.chars().count()).saturating_sub(occurrences * from.chars().count());
+    if length > MAX_LENGTH {
+        return Err(too_long());
+    }
+
+    let mut result = String::new();
+    result.try_reserve((text.len() + occurrences * to.len()).saturating_sub(occurrences * from.len())).map_err(|_| too_long())?;
+    let mut rest = 0;
+    for (start, found) in text.match_indices(from) {
+        result.push_str(&text[rest..start]);
+        result.push_str(to);
+        rest = start + found.len();
+    }
+    result.push_str(&text[rest..]);
+    Ok(Literal::String(result.into_boxed_str()))
+}
+
+/// `concat(text, ...)` joins its arguments, the result is at most [MAX_LENGTH] characters long.
+fn concat(arguments: &[Literal]) -> Result<Literal, EvalError> {
+    let mut length = 0;
+    let mut size = 0;
+    for index in 0..arguments.len() {
+        let text = string(arguments, index)?;
+        length += text.chars().count();
+        size += text.len();
+        if length > MAX_LENGTH {
+            return Err(EvalError::invalid_argument(index, "a string keeping the result at most 1048576 characters long", LiteralType::String));
+        }
+    }
+
+    let mut result = String::new();
+    result.try_reserve(size)
+        .map_err(|_| EvalError::invalid_argument(0, "a string keeping the result at most 1048576 characters long", LiteralType::String))?;
+    for index in 0..arguments.len() {
+        result.push_str(string(arguments, index)?);
+    }
+    Ok(Literal::String(result.into_boxed_str()))
+}
+
+/// `pad_left(text, width)` or `pad_left(text, width, fill)`, pads with spaces unless a single fill character is given.
+/// The width is at most [MAX_LENGTH].
+fn pad(arguments: &[Literal], left: bool) -> Result<Literal, EvalError> {
+    let text = string(arguments, 0)?;
+    let width = count(arguments, 1)?;
+    let too_wide = || EvalError::invalid_argument(1, "a width of at most 1048576", LiteralType::Integer);
+    if width > MAX_LENGTH {
+        return Err(too_wide());
+    }
+    let fill = match arguments.len() {
+        2 => ' ',
+        _ => {
+            let mut characters = string(arguments, 2)?.chars();
+            match (characters.next(), characters.next()) {
+                (Some(fill), None) => fill,
+                _ => return Err(EvalError::invalid_argument(2, "a single character", LiteralType::String)),
+            }
+        }
+    };
+
+    let padding = width.saturating_sub(text.chars().count());
+    let mut result = String::new();
+    result.try_reserve(text.len() + padding * fill.len_utf8()).map_err(|_| too_wide())?;
+    if !left {
+        result.push_str(text);
+    }
+    result.extend(core::iter::repeat_n(fill, padding));
+    if left {
+        result.push_str(text);
+    }
+    Ok(Literal::String(result.into_boxed_str()))
+}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::collections::btree_map::BTreeMap;
+    use rstest::rstest;
+    use crate::compile;
+    use crate::definition::LiteralType;
+    use crate::error::{EvalError, Span};
+    use crate::Literal;
+    use crate::Literal::{Boolean, Integer, List};
+
+
+    fn evaluate(expression: &str) -> Result<Literal, EvalError> {
+        let context = BTreeMap::from([
+            ("name", Literal::String(Box::from("  Straße  "))),
+            ("greeting", Literal::String(Box::from("grüß dich"))),
+            ("csv", Literal::String(Box::from("a,b,,c"))),
+            ("comma", Literal::String(Box::from(","))),
+            ("semicolon", Literal::String(Box::from(";"))),
+            ("eszett", Literal::String(Box::from("ß"))),
+        ]);
+        compile(expression)?.eval(&context)
+    }
+
+    fn string(value: &str) -> Literal {
+        Literal::String(Box::from(value))
+    }
+
+    #[rstest]
+    #[case::len_counts_characters("len(greeting)", Integer(9))]
+    #[case::len_empty("len('')", Integer(0))]
+    #[case::upper("upper(greeting)", string("GRÜSS DICH"))]
+    #[case::lower("lower(trim(name))", string("straße"))]
+    #[case::trim("trim(name)", string("Straße"))]
+    #[case::contains("contains(greeting, 'dich')", Boolean(true))]
+    #[case::starts_with("starts_with(greeting, 'dich')", Boolean(false))]
+    #[case::ends_with("ends_with(greeting, 'dich')", Boolean(true))]
+    #[case::index_of_counts_characters("index_of(greeting, 'dich')", Integer(5))]
+    #[case::index_of_missing("index_of(greeting, 'x')", Integer(-1))]
+    #[case::substr("substr(greeting, 2, 2)", string("üß"))]
+    #[case::substr_to_end("substr(greeting, 5)", string("dich"))]
+    #[case::substr_past_end("substr(greeting, 20, 2)", string(""))]
+    #[case::replace("replace(csv, comma, semicolon)", string("a;b;;c"))]
+    #[case::replace_empty("replace('ab', '', '-')", string("-a-b-"))]
+    #[case::replace_shrinking("len(replace(pad_left('', 1048576), ' ', '')) == 0", Boolean(true))]
+    #[case::split("split(csv, comma)", List(vec![string("a"), string("b"), string(""), string("c")]))]
+    #[case::concat("concat('a', greeting, 'b')", string("agrüß dichb"))]
+    #[case::pad_left("pad_left('ab', 4)", string("  ab"))]
+    #[case::pad_right("pad_right('ab', 4, eszett)", string("abßß"))]
+    #[case::pad_shorter_width("pad_left(greeting, 2)", string("grüß dich"))]
+    #[case::pad_maximum_width("len(pad_left('a', 1048576)) == 1048576", Boolean(true))]
+    #[case::nested("len(trim(name)) == 6", Boolean(true))]
+    fn string_functions(#[case] expression: &str, #[case] expected: Literal) {
+        assert_eq!(evaluate(expression), Ok(expected));
+    }
+
+    #[rstest]
+    #[case::not_a_string("upper(1)", EvalError::InvalidArgument { index: 0, expected: "a string", found: LiteralType::Integer, span: Span::new(6, 7) })]
+    #[case::negative_position("substr(greeting, -1)", EvalError::InvalidArgument { index: 1, expected: "a non-negative integer", found: LiteralType::Integer, span: Span::new(17, 19) })]
+    #[case::empty_separator("split(csv, '')", EvalError::InvalidArgument { index: 1, expected: "a non-empty separator", found: LiteralType::String, span: Span::new(11, 13) })]
+    #[case::huge_width("pad_left('a', 9223372036854775807)", EvalError::InvalidArgument { index: 1, expected: "a width of at most 1048576", found: LiteralType::Integer, span: Span::new(14, 33) })]
+    #[case::width_above_maximum("pad_right('a', 1048577)", EvalError::InvalidArgument { index: 1, expected: "a width of at most 1048576", found: LiteralType::Integer, span: Span::new(15, 22) })]
+    #[case::replace_too_long("replace(pad_left('', 1048576), ' ', 'ab')", EvalError::InvalidArgument { index: 2, expected: "a replacement with a result of at most 1048576 characters", found: LiteralType::String, span: Span::new(36, 40) })]
+    #[case::concat_too_long("concat(pad_left('', 1048576), 'a')", EvalError::InvalidArgument { index: 1, expected: "a string keeping the result at most 1048576 characters long", found: LiteralType::String, span: Span::new(30, 33) })]
+    #[case::fill_too_long("pad_left('a', 3, 'xy')", EvalError::InvalidArgument { index: 2, expected: "a single character", found: LiteralType::String, span: Span::new(17, 21) })]
+    fn string_function_errors(#[case] expression: &str, #[case] expected: EvalError) {
+        assert_eq!(evaluate(expression), Err(expected));
+    }
+}
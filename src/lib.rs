@@ -4,45 +4,105 @@
 // for the tests lets bring assert_matches and the std crate into scope
 #[cfg(test)]
 #[macro_use]
-extern crate assert_matches;
+extern crate std;
 #[cfg(test)]
 #[macro_use]
-extern crate std;
+extern crate assert_matches;
 // #[cfg(test)] use std::prelude::*;
 extern crate alloc;
 
 use alloc::collections::btree_map::BTreeMap;
-use crate::context::replace_variables_with_values_from_context;
-use crate::converter::convert_infix_to_postfix_notation;
-use crate::definition::{Literal, Token};
-use crate::eval::evaluate_tokens;
-use crate::tokenizer::string_to_tokens;
+use crate::ast::Expr;
+use crate::compiled::CompiledExpression;
+use crate::context::{Context, EmptyContext, parse_value, resolve_with};
+use crate::derived::DerivedVariables;
+use crate::definition::Literal;
+use crate::error::{EvalError, ParseError};
+use crate::path::VariablePath;
+use crate::function::FunctionRegistry;
+use crate::options::EvalOptions;
+use crate::parser::parse_tokens;
+use crate::tokenizer::{string_to_tokens, string_to_tokens_with_mode};
 
 mod tokenizer;
 mod eval;
-mod converter;
-mod context;
+mod parser;
+pub mod ast;
+pub mod compiled;
+pub mod context;
 pub mod definition;
+pub mod derived;
+pub mod diagnostic;
+pub mod error;
+pub mod function;
+pub mod options;
+pub mod path;
 
 
-pub fn evaluate_expression(expression_string: &str) -> Result<Literal, &'static str> {
+pub fn parse(expression_string: &str) -> Result<Expr, ParseError> {
     string_to_tokens(expression_string)
-        .and_then(convert_infix_to_postfix_notation)
-        .and_then(evaluate_tokens)
+        .and_then(parse_tokens)
 }
 
 
-pub fn evaluate_expression_with_context(expression_string: &str, context: &BTreeMap<&str, &str>) -> Result<Literal, &'static str> {
-    let result = string_to_tokens(expression_string);
+/// Tokenizes and parses the expression once, so it can be evaluated against many contexts.
+pub fn compile(expression_string: &str) -> Result<CompiledExpression, ParseError> {
+    compile_with(expression_string, &EvalOptions::default(), &FunctionRegistry::with_builtins())
+}
 
-    match result {
-        Ok(tokens) => {
-            replace_variables_with_values_from_context(tokens, context)
-                .and_then(convert_infix_to_postfix_notation)
-                .and_then(evaluate_tokens)
-        }
-        Err(e) => Err(e)
-    }
+pub fn compile_with_options(expression_string: &str, options: &EvalOptions) -> Result<CompiledExpression, ParseError> {
+    compile_with(expression_string, options, &FunctionRegistry::with_builtins())
+}
+
+/// Compiles an expression which may call the given functions. Unknown functions and calls with
+/// an unsupported number of arguments are reported here instead of during evaluation.
+pub fn compile_with_functions(expression_string: &str, functions: &FunctionRegistry) -> Result<CompiledExpression, ParseError> {
+    compile_with(expression_string, &EvalOptions::default(), functions)
+}
+
+/// Compiles an expression which may refer to the given derived variables, see [crate::derived]. The functions called
+/// by the definitions are validated like the ones called by the expression itself.
+pub fn compile_with_derived(expression_string: &str, derived: &DerivedVariables, options: &EvalOptions, functions: &FunctionRegistry) -> Result<CompiledExpression, ParseError> {
+    let expression = string_to_tokens_with_mode(expression_string, options.lex_mode)
+        .and_then(parse_tokens)?;
+    compiled(expression_string, derived.expand(expression, options.lex_mode)?, options, functions)
+}
+
+pub fn compile_with(expression_string: &str, options: &EvalOptions, functions: &FunctionRegistry) -> Result<CompiledExpression, ParseError> {
+    let expression = string_to_tokens_with_mode(expression_string, options.lex_mode)
+        .and_then(parse_tokens)?;
+    compiled(expression_string, expression, options, functions)
+}
+
+fn compiled(expression_string: &str, expression: Expr, options: &EvalOptions, functions: &FunctionRegistry) -> Result<CompiledExpression, ParseError> {
+    functions.validate(&expression)?;
+
+    Ok(CompiledExpression::new(expression_string, expression, *options, functions.clone()))
+}
+
+
+pub fn evaluate_expression(expression_string: &str) -> Result<Literal, EvalError> {
+    compile(expression_string)
+        .and_then(|expression| expression.eval(&EmptyContext))
+}
+
+pub fn evaluate_expression_with_options(expression_string: &str, options: &EvalOptions) -> Result<Literal, EvalError> {
+    compile_with_options(expression_string, options)
+        .and_then(|expression| expression.eval(&EmptyContext))
+}
+
+
+/// Evaluates the expression with the variables resolved from any [Context], e.g. a map of literals or a closure.
+pub fn evaluate_expression_with_values<C: Context + ?Sized>(expression_string: &str, context: &C) -> Result<Literal, EvalError> {
+    compile(expression_string)
+        .and_then(|expression| expression.eval(context))
+}
+
+/// Evaluates the expression with the variables taken from a map of strings, see [parse_value] for how the
+/// strings are read. Prefer [evaluate_expression_with_values], which takes typed values.
+pub fn evaluate_expression_with_context(expression_string: &str, context: &BTreeMap<&str, &str>) -> Result<Literal, EvalError> {
+    let values = |path: &VariablePath| resolve_with(path, |name| context.get(name).map(|value| parse_value(value)));
+    evaluate_expression_with_values(expression_string, &values)
 }
 
 
@@ -51,8 +111,12 @@ mod tests {
     extern crate alloc;
 
     use alloc::collections::btree_map::BTreeMap;
-    use crate::evaluate_expression_with_context;
-    use crate::Literal::{Boolean, Decimal};
+    use crate::{evaluate_expression, evaluate_expression_with_context, evaluate_expression_with_options, evaluate_expression_with_values};
+    use crate::path::VariablePath;
+    use crate::definition::{LiteralType, Operator};
+    use crate::error::{EvalError, LexErrorKind, Span};
+    use crate::options::{EvalOptions, LexMode};
+    use crate::Literal::{Boolean, Decimal, Integer};
 
 
     #[test]
@@ -75,4 +139,39 @@ mod tests {
 
         assert_eq!(Ok(Boolean(true)), result);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn context_values_are_not_expressions() {
+        let context = BTreeMap::from([("first", "4.5 > 3"), ("second", "true")]);
+
+        assert_eq!(evaluate_expression_with_context("first == '4.5 > 3'", &context), Ok(Boolean(true)));
+        assert_eq!(evaluate_expression_with_context("first == second", &context), Err(EvalError::TypeMismatch { operator: Operator::Equal, left: LiteralType::String, right: LiteralType::Boolean, span: Span::new(0, 15) }));
+    }
+
+    #[test]
+    fn missing_context_variable_is_an_error() {
+        let context = BTreeMap::new();
+
+        let result = evaluate_expression_with_context("missing == 'A'", &context);
+
+        assert_eq!(Err(EvalError::UnknownVariable { name: "missing".into(), span: Span::new(0, 7) }), result);
+    }
+
+    #[test]
+    fn lex_mode_from_options() {
+        let strict = evaluate_expression("3 + 4 $");
+        let lenient = evaluate_expression_with_options("3 + 4 $", &EvalOptions::default().with_lex_mode(LexMode::Lenient));
+
+        assert_eq!(Err(EvalError::Lex { kind: LexErrorKind::UnexpectedCharacter('$'), span: Span::new(6, 7) }), strict);
+        assert_eq!(Ok(Integer(7)), lenient);
+    }
+
+    #[test]
+    fn typed_context_evaluation() {
+        let context = BTreeMap::from([("a", Decimal(4.5)), ("b", Integer(3))]);
+        assert_eq!(evaluate_expression_with_values("a > b", &context), Ok(Boolean(true)));
+
+        let context = |path: &VariablePath| (path.root() == "a").then_some(Integer(2));
+        assert_eq!(evaluate_expression_with_values("a * a", &context), Ok(Integer(4)));
+    }
+}
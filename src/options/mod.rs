@@ -0,0 +1,231 @@
+//!
+//! Options which control how an expression is tokenized and evaluated.
+
+
+/// Controls how the tokenizer treats characters which are not part of the expression grammar.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum LexMode {
+    /// Unknown characters are reported as [crate::error::LexErrorKind::UnexpectedCharacter].
+    #[default]
+    Strict,
+    /// Unknown characters are skipped, as older versions of this crate did.
+    Lenient,
+}
+
+
+/// Controls which operands of different types are converted before an operator is applied.
+///
+/// | operator | left                         | right                        | Strict          | Lenient         |
+/// |----------|------------------------------|------------------------------|-----------------|-----------------|
+/// | any      | integer                      | decimal                      | decimal         | decimal         |
+/// | any      | integer                      | money                        | money           | money           |
+/// | `+`      | string                       | string                       | string          | string          |
+/// | `+`      | string                       | integer, decimal or boolean  | type mismatch   | string          |
+/// | `+`      | integer, decimal or boolean  | string                       | type mismatch   | string          |
+/// | any      | any other pair of types      |                              | type mismatch   | type mismatch   |
+///
+/// Values converted to strings are written as they are printed, e.g. `'v' + 1.0` is `'v1.0'`. Money, which requires
+/// the `decimal` feature, is never mixed with decimals, as that would make its results inexact again.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Coercion {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+
+/// Controls what a variable which is not provided by the context evaluates to.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum MissingVariables {
+    /// Missing variables are reported as [crate::error::EvalError::UnknownVariable].
+    #[default]
+    Error,
+    /// Missing variables are [crate::definition::Literal::Null].
+    Null,
+}
+
+
+/// Controls how operators treat null operands. Arithmetic on null is null and `is null` / `is not null` are
+/// always true or false, whichever logic is chosen.
+///
+/// | expression                   | ThreeValued | NullIsFalse |
+/// |------------------------------|-------------|-------------|
+/// | `null + 1`, `-null`          | null        | null        |
+/// | `null == null`               | null        | true        |
+/// | `null == 1`, `null < 1`      | null        | false       |
+/// | `null != 1`                  | null        | true        |
+/// | `!null`                      | null        | true        |
+/// | `null && true`               | null        | false       |
+/// | `null && false`              | false       | false       |
+/// | `null \|\| true`               | true        | true        |
+/// | `null \|\| false`              | null        | false       |
+/// | `null is null`               | true        | true        |
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum NullLogic {
+    /// SQL like logic, null is an unknown value and every result depending on it is unknown as well.
+    #[default]
+    ThreeValued,
+    /// Null is false in `&&`, `||` and `!`, it is only equal to null and never less or greater than anything.
+    NullIsFalse,
+}
+
+
+/// Controls the sign of `%` and the rounding of `//` for negative operands. Both are chosen together, so
+/// `a == (a // b) * b + a % b` holds in either mode.
+///
+/// | expression  | Truncated | Euclidean |
+/// |-------------|-----------|-----------|
+/// | `7 % 3`     | 1         | 1         |
+/// | `-7 % 3`    | -1        | 2         |
+/// | `7 % -3`    | 1         | 1         |
+/// | `-7 // 3`   | -2        | -3        |
+/// | `7 // -3`   | -2        | -2        |
+/// | `-7.5 % 2`  | -1.5      | 0.5       |
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Modulo {
+    /// The quotient is rounded towards zero and the remainder has the sign of the dividend, like Rust's `/` and `%`.
+    #[default]
+    Truncated,
+    /// The remainder is never negative, like [i64::rem_euclid] and [i64::div_euclid].
+    Euclidean,
+}
+
+
+/// Controls the result of integer operations which do not fit into 64 bits, e.g. `9223372036854775807 + 1`.
+/// Applies to `+`, `-`, `*`, `/`, `//`, `^` and unary `-`, shifts by a negative amount or by more than 63 bits
+/// and overflows in functions like `abs` are an error in every policy.
+///
+/// | expression                 | Error    | Wrapping             | Saturating           | PromoteToDecimal      |
+/// |----------------------------|----------|----------------------|----------------------|-----------------------|
+/// | `9223372036854775807 + 1`  | overflow | -9223372036854775808 | 9223372036854775807  | 9.223372036854776e18  |
+/// | `-3037000500 * 3037000500` | overflow | 9223372036709301616  | -9223372036854775808 | -9.22337203700025e18  |
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum IntegerOverflow {
+    /// The operation fails with [crate::error::EvalError::Overflow].
+    #[default]
+    Error,
+    /// The result wraps around at the boundary, like [i64::wrapping_add].
+    Wrapping,
+    /// The result is clamped to [i64::MIN] or [i64::MAX].
+    Saturating,
+    /// The result is calculated with decimals instead, losing precision beyond 53 bits.
+    PromoteToDecimal,
+}
+
+
+/// Controls the result of dividing by zero with `/`, `//` and `%`, for integers as well as for decimals.
+///
+/// | expression              | Error            | Ieee   | Null |
+/// |-------------------------|------------------|--------|------|
+/// | `1 / 0`, `1.0 / 0.0`    | division by zero | `inf`  | null |
+/// | `-1 / 0`                | division by zero | `-inf` | null |
+/// | `0 / 0`, `1 % 0`        | division by zero | `NaN`  | null |
+///
+/// Infinite and NaN decimals compare as defined by IEEE 754, e.g. NaN is not even equal to itself,
+/// the built-in functions `is_nan` and `is_finite` check for them explicitly. Money has no such values, dividing it
+/// by zero stays an error with Ieee.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum ZeroDivision {
+    /// The operation fails with [crate::error::EvalError::DivisionByZero].
+    #[default]
+    Error,
+    /// The result is the decimal IEEE 754 defines for the division, integer operands included.
+    Ieee,
+    /// The result is [crate::definition::Literal::Null].
+    Null,
+}
+
+
+/// Controls how money results are rounded to [EvalOptions::money_scale] fraction digits.
+///
+/// | value     | HalfEven | HalfUp | HalfDown | Up    | Down  | Ceiling | Floor |
+/// |-----------|----------|--------|----------|-------|-------|---------|-------|
+/// | `2.345d`  | 2.34     | 2.35   | 2.34     | 2.35  | 2.34  | 2.35    | 2.34  |
+/// | `2.355d`  | 2.36     | 2.36   | 2.35     | 2.36  | 2.35  | 2.36    | 2.35  |
+/// | `-2.345d` | -2.34    | -2.35  | -2.34    | -2.35 | -2.34 | -2.34   | -2.35 |
+#[cfg(feature = "decimal")]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Rounding {
+    /// Ties go to the even neighbour, also known as banker's rounding.
+    #[default]
+    HalfEven,
+    /// Ties go away from zero, as taught in school.
+    HalfUp,
+    /// Ties go towards zero.
+    HalfDown,
+    /// Away from zero.
+    Up,
+    /// Towards zero, i.e. the surplus digits are cut off.
+    Down,
+    /// Towards positive infinity.
+    Ceiling,
+    /// Towards negative infinity.
+    Floor,
+}
+
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct EvalOptions {
+    pub lex_mode: LexMode,
+    pub coercion: Coercion,
+    pub missing_variables: MissingVariables,
+    pub null_logic: NullLogic,
+    pub modulo: Modulo,
+    pub integer_overflow: IntegerOverflow,
+    pub zero_division: ZeroDivision,
+    /// Number of fraction digits money results of operators are rounded to, unlimited if `None`. Without a scale
+    /// `+`, `-` and `*` are exact and `/` keeps 28 significant digits.
+    #[cfg(feature = "decimal")]
+    pub money_scale: Option<u32>,
+    #[cfg(feature = "decimal")]
+    pub money_rounding: Rounding,
+}
+
+impl EvalOptions {
+    pub fn with_lex_mode(mut self, lex_mode: LexMode) -> Self {
+        self.lex_mode = lex_mode;
+        self
+    }
+
+    pub fn with_coercion(mut self, coercion: Coercion) -> Self {
+        self.coercion = coercion;
+        self
+    }
+
+    pub fn with_missing_variables(mut self, missing_variables: MissingVariables) -> Self {
+        self.missing_variables = missing_variables;
+        self
+    }
+
+    pub fn with_null_logic(mut self, null_logic: NullLogic) -> Self {
+        self.null_logic = null_logic;
+        self
+    }
+
+    pub fn with_modulo(mut self, modulo: Modulo) -> Self {
+        self.modulo = modulo;
+        self
+    }
+
+    pub fn with_integer_overflow(mut self, integer_overflow: IntegerOverflow) -> Self {
+        self.integer_overflow = integer_overflow;
+        self
+    }
+
+    pub fn with_zero_division(mut self, zero_division: ZeroDivision) -> Self {
+        self.zero_division = zero_division;
+        self
+    }
+
+    #[cfg(feature = "decimal")]
+    pub fn with_money_scale(mut self, money_scale: u32) -> Self {
+        self.money_scale = Some(money_scale);
+        self
+    }
+
+    #[cfg(feature = "decimal")]
+    pub fn with_money_rounding(mut self, money_rounding: Rounding) -> Self {
+        self.money_rounding = money_rounding;
+        self
+    }
+}
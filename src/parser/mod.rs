@@ -0,0 +1,516 @@
+//!
+//! Pratt parser which turns the tokens of an expression into an [Expr] tree.
+//!
+//! Infix and postfix operators bind and group according to their row in [crate::definition::OPERATORS],
+//! prefix operators bind their operand with their own precedence. The conditional `a ? b : c` binds weaker than
+//! any operator and groups from the right, the keyword form `if a then b else c` extends as far to the right as
+//! possible. Mismatched parenthesis, missing operands
+//! and operators used in the wrong position are reported while parsing.
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::iter::Peekable;
+use alloc::vec::IntoIter;
+use crate::ast::{Expr, MAX_DEPTH};
+use crate::definition::{Associativity, Fixity, Keyword, Parenthesis, SpannedToken, Token};
+use crate::error::{EvalError, ParseError, Span};
+
+
+/// Below the precedence of every operator, so the branches of a conditional may contain any operator.
+const CONDITIONAL_PRECEDENCE: i8 = 0;
+
+
+pub fn parse_tokens(tokens: Vec<SpannedToken>) -> Result<Expr, ParseError> {
+    let end_of_input = tokens.last().map_or(0, |spanned| spanned.span.end);
+    let mut parser = Parser { tokens: tokens.into_iter().peekable(), end_of_input, depth: 0 };
+
+    let (expression, _) = parser.parse_expression(CONDITIONAL_PRECEDENCE)?;
+
+    match parser.tokens.next() {
+        None => Ok(expression),
+        Some(SpannedToken { token: Token::Parenthesis(Parenthesis::RightParenthesis), span }) => {
+            Err(EvalError::UnbalancedParenthesis { span })
+        }
+        Some(SpannedToken { span, .. }) => {
+            Err(EvalError::MissingOperator { span })
+        }
+    }
+}
+
+
+/// An expression together with its height, the number of nodes on the longest path from it to a leaf.
+type Parsed = (Expr, usize);
+
+/// Accepts a new node of the given height, unless it nests deeper than [MAX_DEPTH]. Checking every node as it is
+/// built also catches chains like `1 + 1 + …`, which grow without recursing in the parser.
+fn node(expression: Expr, height: usize) -> Result<Parsed, ParseError> {
+    match height > MAX_DEPTH {
+        true => Err(EvalError::TooDeep { span: expression.span() }),
+        false => Ok((expression, height)),
+    }
+}
+
+
+struct Parser {
+    tokens: Peekable<IntoIter<SpannedToken>>,
+    end_of_input: usize,
+    /// Number of nested [Parser::parse_expression] calls, limited to [MAX_DEPTH] to protect the stack.
+    depth: usize,
+}
+
+impl Parser {
+    fn parse_expression(&mut self, minimum_precedence: i8) -> Result<Parsed, ParseError> {
+        if self.depth == MAX_DEPTH {
+            let span = self.tokens.peek().map_or(Span::new(self.end_of_input, self.end_of_input), |spanned| spanned.span);
+            return Err(EvalError::TooDeep { span });
+        }
+        self.depth += 1;
+        let parsed = self.parse_operators(minimum_precedence);
+        self.depth -= 1;
+        parsed
+    }
+
+    fn parse_operators(&mut self, minimum_precedence: i8) -> Result<Parsed, ParseError> {
+        let (mut left, mut height) = self.parse_prefix()?;
+
+        while let Some(SpannedToken { token, span }) = self.tokens.peek() {
+            let (properties, operator_span) = match token {
+                Token::Operator(properties) => (*properties, *span),
+                Token::QuestionMark if minimum_precedence <= CONDITIONAL_PRECEDENCE => {
+                    self.tokens.next();
+                    (left, height) = self.parse_conditional_branches(left.span().start, (left, height), Token::Colon, ":")?;
+                    continue;
+                }
+                _ => break,
+            };
+            if properties.fixity == Fixity::Prefix {
+                return Err(EvalError::ArityMismatch { operator: properties.operator, span: operator_span });
+            }
+            if properties.precedence < minimum_precedence {
+                break;
+            }
+            self.tokens.next();
+
+            if properties.fixity == Fixity::Postfix {
+                let span = Span::new(left.span().start, operator_span.end);
+                (left, height) = node(Expr::Unary { operator: properties.operator, operand: Box::new(left), span }, height + 1)?;
+                continue;
+            }
+
+            let next_minimum_precedence = match properties.associativity {
+                Associativity::Left => properties.precedence + 1,
+                Associativity::Right => properties.precedence,
+            };
+            let (right, right_height) = self.parse_expression(next_minimum_precedence)?;
+            let span = Span::new(left.span().start, right.span().end);
+
+            (left, height) = node(Expr::Binary { operator: properties.operator, left: Box::new(left), right: Box::new(right), span }, height.max(right_height) + 1)?;
+        }
+
+        Ok((left, height))
+    }
+
+    fn parse_prefix(&mut self) -> Result<Parsed, ParseError> {
+        let SpannedToken { token, span } = self.tokens.next()
+            .ok_or(EvalError::MissingOperand { span: Span::new(self.end_of_input, self.end_of_input) })?;
+
+        match token {
+            Token::Literal(value) => {
+                Ok((Expr::Literal { value, span }, 1))
+            }
+            Token::Variable(path) if path.as_identifier().is_some() && self.next_is(&Token::Parenthesis(Parenthesis::LeftParenthesis)) => {
+                self.parse_call(Box::from(path.root()), span)
+            }
+            Token::Variable(path) => {
+                Ok((Expr::Variable { path, span }, 1))
+            }
+            Token::Operator(properties) if properties.fixity == Fixity::Prefix => {
+                let (operand, height) = self.parse_expression(properties.precedence)?;
+                let span = Span::new(span.start, operand.span().end);
+                node(Expr::Unary { operator: properties.operator, operand: Box::new(operand), span }, height + 1)
+            }
+            Token::Keyword(Keyword::If) => {
+                let condition = self.parse_expression(CONDITIONAL_PRECEDENCE)?;
+                self.expect_conditional_part(Token::Keyword(Keyword::Then), "then")?;
+                self.parse_conditional_branches(span.start, condition, Token::Keyword(Keyword::Else), "else")
+            }
+            Token::Parenthesis(Parenthesis::LeftParenthesis) => {
+                let (inner, height) = self.parse_expression(CONDITIONAL_PRECEDENCE)?;
+                let closing = self.expect_closing_parenthesis(span)?;
+                node(Expr::Grouping { inner: Box::new(inner), span: Span::new(span.start, closing.end) }, height + 1)
+            }
+            Token::Operator(_) | Token::Parenthesis(Parenthesis::RightParenthesis) | Token::Comma => {
+                Err(EvalError::MissingOperand { span })
+            }
+            Token::QuestionMark | Token::Colon | Token::Keyword(Keyword::Then | Keyword::Else) => {
+                Err(EvalError::MissingOperand { span })
+            }
+        }
+    }
+
+    fn parse_call(&mut self, name: Box<str>, name_span: Span) -> Result<Parsed, ParseError> {
+        let opening = self.tokens.next().map_or(name_span, |spanned| spanned.span);
+        let mut arguments = Vec::new();
+        let mut height = 0;
+
+        if !self.next_is(&Token::Parenthesis(Parenthesis::RightParenthesis)) {
+            loop {
+                let (argument, argument_height) = self.parse_expression(CONDITIONAL_PRECEDENCE)?;
+                arguments.push(argument);
+                height = height.max(argument_height);
+                if !self.next_is(&Token::Comma) {
+                    break;
+                }
+                self.tokens.next();
+            }
+        }
+
+        let closing = self.expect_closing_parenthesis(opening)?;
+        node(Expr::Call { name, arguments, span: Span::new(name_span.start, closing.end) }, height + 1)
+    }
+
+    /// Parses both branches of a conditional starting at `start`, whose condition and `?` or `then` are already
+    /// consumed. The branches are separated by `:` or by `else`.
+    fn parse_conditional_branches(&mut self, start: usize, condition: Parsed, separator: Token, symbol: &'static str) -> Result<Parsed, ParseError> {
+        let (condition, condition_height) = condition;
+        let (then_branch, then_height) = self.parse_expression(CONDITIONAL_PRECEDENCE)?;
+        self.expect_conditional_part(separator, symbol)?;
+        let (else_branch, else_height) = self.parse_expression(CONDITIONAL_PRECEDENCE)?;
+
+        let span = Span::new(start, else_branch.span().end);
+        let height = condition_height.max(then_height).max(else_height) + 1;
+        node(Expr::Conditional { condition: Box::new(condition), then_branch: Box::new(then_branch), else_branch: Box::new(else_branch), span }, height)
+    }
+
+    fn expect_conditional_part(&mut self, expected: Token, symbol: &'static str) -> Result<Span, ParseError> {
+        match self.tokens.next() {
+            Some(SpannedToken { token, span }) if token == expected => Ok(span),
+            Some(SpannedToken { span, .. }) => Err(EvalError::IncompleteConditional { expected: symbol, span }),
+            None => Err(EvalError::IncompleteConditional { expected: symbol, span: Span::new(self.end_of_input, self.end_of_input) }),
+        }
+    }
+
+    fn expect_closing_parenthesis(&mut self, opening: Span) -> Result<Span, ParseError> {
+        match self.tokens.next() {
+            Some(SpannedToken { token: Token::Parenthesis(Parenthesis::RightParenthesis), span }) => Ok(span),
+            Some(SpannedToken { span, .. }) => Err(EvalError::MissingOperator { span }),
+            None => Err(EvalError::UnbalancedParenthesis { span: opening }),
+        }
+    }
+
+    fn next_is(&mut self, expected: &Token) -> bool {
+        self.tokens.peek().is_some_and(|spanned| spanned.token == *expected)
+    }
+}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use std::boxed::Box;
+    use std::format;
+    use std::string::{String, ToString};
+    use proptest::prelude::*;
+    use rstest::rstest;
+    use crate::ast::{Expr, MAX_DEPTH};
+    use crate::definition::Operator;
+    use crate::error::{EvalError, Span};
+    use crate::Literal::Integer;
+    use crate::{evaluate_expression, parse};
+
+
+    #[test]
+    fn parse_binary_expression_with_spans() {
+        let expression = parse("1 + 22").unwrap();
+
+        let expected = Expr::Binary {
+            operator: Operator::Plus,
+            left: Expr::Literal { value: Integer(1), span: Span::new(0, 1) }.into(),
+            right: Expr::Literal { value: Integer(22), span: Span::new(4, 6) }.into(),
+            span: Span::new(0, 6),
+        };
+        assert_eq!(expression, expected);
+    }
+
+    #[rstest]
+    #[case::precedence("3 + 4 × 2 ÷ ( 1 − 5 ) ^ 2 ^ 3", "3 + 4 * 2 / (1 - 5) ^ 2 ^ 3")]
+    #[case::without_whitespace("(1+2)*a", "(1 + 2) * a")]
+    #[case::logical("3 <= 3 && 23.8 >= 23.8", "3 <= 3 && 23.8 >= 23.8")]
+    #[case::prefix("-2 ^ 2 * -x", "-2 ^ 2 * -x")]
+    #[case::negated_group("!(a > b) && c", "!(a > b) && c")]
+    #[case::call("max(a, 2 * b) > 3", "max(a, 2 * b) > 3")]
+    #[case::call_without_arguments("now()", "now()")]
+    #[case::coalesce("a??null ?? 1", "a ?? null ?? 1")]
+    #[case::paths("items[0].name + tags[\"env\"]", "items[0].name + tags['env']")]
+    #[case::bitwise("a & 1 == 1 || b | c xor d & e << 2", "a & 1 == 1 || b | c xor d & e << 2")]
+    #[case::modulo("-a % 3 // 2", "-a % 3 // 2")]
+    #[case::bit_not("~a & ~-1", "~a & ~-1")]
+    #[case::conditional("qty > 100 ? price * 0.9 : price", "if qty > 100 then price * 0.9 else price")]
+    #[case::conditional_groups_from_the_right("a ? 1 : b ? 2 : 3", "if a then 1 else if b then 2 else 3")]
+    #[case::conditional_in_then_branch("a ? b ? 1 : 2 : 3", "if a then if b then 1 else 2 else 3")]
+    #[case::conditional_after_coalesce("a ?? b?c:d", "if a ?? b then c else d")]
+    #[case::keyword_conditional("1 + if a then 2 else 3 * 4", "1 + if a then 2 else 3 * 4")]
+    #[case::keyword_conditional_chain("if a then 1 else if b then 2 else 3", "if a then 1 else if b then 2 else 3")]
+    #[case::conditional_argument("max(a ? 1 : 2, 3)", "max(if a then 1 else 2, 3)")]
+    #[case::null_tests("a + 1 is null || b is  not null", "a + 1 is null || b is not null")]
+    fn parse_and_print(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(parse(input).unwrap().to_string(), expected);
+    }
+
+    #[test]
+    fn parse_structure_follows_precedence() {
+        let expression = parse("2 ^ 3 ^ 2 - 1 - 1").unwrap();
+
+        assert_matches!(expression, Expr::Binary { operator: Operator::Minus, left, .. } => {
+            assert_matches!(*left, Expr::Binary { operator: Operator::Minus, left, .. } => {
+                assert_matches!(*left, Expr::Binary { operator: Operator::PowerOf, right, .. } => {
+                    assert_matches!(*right, Expr::Binary { operator: Operator::PowerOf, .. });
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn parse_bitwise_precedence() {
+        // comparisons bind weakest, then |, xor, &, the shifts and finally arithmetic
+        let expression = parse("a | b xor c & d << 1 + 1 == 0").unwrap();
+
+        assert_matches!(expression, Expr::Binary { operator: Operator::Equal, left, .. } => {
+            assert_matches!(*left, Expr::Binary { operator: Operator::BitOr, right, .. } => {
+                assert_matches!(*right, Expr::Binary { operator: Operator::BitXor, right, .. } => {
+                    assert_matches!(*right, Expr::Binary { operator: Operator::BitAnd, right, .. } => {
+                        assert_matches!(*right, Expr::Binary { operator: Operator::ShiftLeft, right, .. } => {
+                            assert_matches!(*right, Expr::Binary { operator: Operator::Plus, .. });
+                        });
+                    });
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn parse_null_test_precedence() {
+        assert_matches!(parse("a == b is null").unwrap(), Expr::Binary { operator: Operator::Equal, right, .. } => {
+            assert_matches!(*right, Expr::Unary { operator: Operator::IsNull, .. });
+        });
+        assert_matches!(parse("a < b is not null").unwrap(), Expr::Unary { operator: Operator::IsNotNull, operand, .. } => {
+            assert_matches!(*operand, Expr::Binary { operator: Operator::Less, .. });
+        });
+        assert_matches!(parse("a is null is null").unwrap(), Expr::Unary { operator: Operator::IsNull, operand, .. } => {
+            assert_matches!(*operand, Expr::Unary { operator: Operator::IsNull, .. });
+        });
+    }
+
+    #[test]
+    fn parse_conditional_structure() {
+        let expression = parse("a || b ? 1 : c ? 2 : 3").unwrap();
+
+        assert_matches!(expression, Expr::Conditional { condition, else_branch, span, .. } => {
+            assert_eq!(span, Span::new(0, 22));
+            assert_matches!(*condition, Expr::Binary { operator: Operator::Or, .. });
+            assert_matches!(*else_branch, Expr::Conditional { span, .. } => {
+                assert_eq!(span, Span::new(13, 22));
+            });
+        });
+        assert_eq!(parse("1 + if a then 2 else 3").unwrap().span(), Span::new(0, 22));
+    }
+
+    #[rstest]
+    #[case::missing_closing("(1 + 2", EvalError::UnbalancedParenthesis { span: Span::new(0, 1) })]
+    #[case::missing_opening("1 + 2)", EvalError::UnbalancedParenthesis { span: Span::new(5, 6) })]
+    #[case::missing_closing_call("max(1, 2", EvalError::UnbalancedParenthesis { span: Span::new(3, 4) })]
+    #[case::missing_outer_closing("((1) + 2", EvalError::UnbalancedParenthesis { span: Span::new(0, 1) })]
+    #[case::missing_inner_opening("(1) + 2)", EvalError::UnbalancedParenthesis { span: Span::new(7, 8) })]
+    #[case::closing_before_opening(")1(", EvalError::MissingOperand { span: Span::new(0, 1) })]
+    #[case::missing_operand("3 +", EvalError::MissingOperand { span: Span::new(3, 3) })]
+    #[case::leading_binary_operator("* 3", EvalError::MissingOperand { span: Span::new(0, 1) })]
+    #[case::consecutive_binary_operators("1 * / 3", EvalError::MissingOperand { span: Span::new(4, 5) })]
+    #[case::binary_operator_before_closing("(1 *) 3", EvalError::MissingOperand { span: Span::new(4, 5) })]
+    #[case::empty_group("()", EvalError::MissingOperand { span: Span::new(1, 2) })]
+    #[case::empty("", EvalError::MissingOperand { span: Span::new(0, 0) })]
+    #[case::missing_operator("1 2", EvalError::MissingOperator { span: Span::new(2, 3) })]
+    #[case::missing_operator_in_group("(1 2)", EvalError::MissingOperator { span: Span::new(3, 4) })]
+    #[case::prefix_operator_in_infix_position("1 ! 2", EvalError::ArityMismatch { operator: Operator::Not, span: Span::new(2, 3) })]
+    #[case::postfix_operator_in_prefix_position("is null", EvalError::MissingOperand { span: Span::new(0, 7) })]
+    #[case::missing_colon("a ? 1", EvalError::IncompleteConditional { expected: ":", span: Span::new(5, 5) })]
+    #[case::missing_colon_before_operand("a ? 1 2", EvalError::IncompleteConditional { expected: ":", span: Span::new(6, 7) })]
+    #[case::missing_then("if a 1 else 2", EvalError::IncompleteConditional { expected: "then", span: Span::new(5, 6) })]
+    #[case::missing_else("if a then 1", EvalError::IncompleteConditional { expected: "else", span: Span::new(11, 11) })]
+    #[case::colon_in_keyword_conditional("if a then 1 : 2", EvalError::IncompleteConditional { expected: "else", span: Span::new(12, 13) })]
+    #[case::missing_condition("? 1 : 2", EvalError::MissingOperand { span: Span::new(0, 1) })]
+    #[case::colon_without_condition("a : b", EvalError::MissingOperator { span: Span::new(2, 3) })]
+    #[case::operand_after_postfix_operator("a is null b", EvalError::MissingOperator { span: Span::new(10, 11) })]
+    fn parse_errors(#[case] input: &str, #[case] expected: EvalError) {
+        assert_eq!(parse(input), Err(expected));
+    }
+
+    #[test]
+    fn reject_deep_nesting() {
+        let parenthesis = |count: usize| format!("{}1{}", "(".repeat(count), ")".repeat(count));
+        let negations = |count: usize| format!("{}1", "-".repeat(count));
+        let sum = |count: usize| format!("1{}", " + 1".repeat(count));
+        let powers = |count: usize| format!("1{}", " ^ 1".repeat(count));
+
+        assert_eq!(evaluate_expression(&parenthesis(MAX_DEPTH - 1)), Ok(Integer(1)));
+        assert_eq!(evaluate_expression(&negations(MAX_DEPTH - 1)), Ok(Integer(-1)));
+        assert_eq!(evaluate_expression(&sum(MAX_DEPTH - 1)), Ok(Integer(MAX_DEPTH as i64)));
+        assert_eq!(evaluate_expression(&powers(MAX_DEPTH - 1)), Ok(Integer(1)));
+
+        assert_eq!(parse(&parenthesis(MAX_DEPTH)), Err(EvalError::TooDeep { span: Span::new(MAX_DEPTH, MAX_DEPTH + 1) }));
+        assert_eq!(parse(&negations(MAX_DEPTH)), Err(EvalError::TooDeep { span: Span::new(MAX_DEPTH, MAX_DEPTH + 1) }));
+        assert_eq!(parse(&sum(MAX_DEPTH)), Err(EvalError::TooDeep { span: Span::new(0, 4 * MAX_DEPTH + 1) }));
+        assert_eq!(parse(&powers(MAX_DEPTH)).map_err(|error| matches!(error, EvalError::TooDeep { .. })), Err(true));
+        assert_eq!(parse(&parenthesis(100_000)).map_err(|error| matches!(error, EvalError::TooDeep { .. })), Err(true));
+        assert_eq!(parse(&sum(100_000)).map_err(|error| matches!(error, EvalError::TooDeep { .. })), Err(true));
+    }
+
+
+    /// Binary operators of the reference evaluator with their precedence and whether they group from the right,
+    /// written down independently of [crate::definition::OPERATORS]. Prefix operators bind tighter than all of them
+    /// except `^`.
+    const REFERENCE_OPERATORS: [(&str, u8, bool); 12] = [
+        ("|", 1, false),
+        ("xor", 2, false),
+        ("&", 3, false),
+        ("<<", 4, false),
+        (">>", 4, false),
+        ("+", 5, false),
+        ("-", 5, false),
+        ("*", 6, false),
+        ("/", 6, false),
+        ("//", 6, false),
+        ("%", 6, false),
+        ("^", 8, true),
+    ];
+    const REFERENCE_PREFIX_PRECEDENCE: u8 = 7;
+
+    #[derive(Debug, Clone)]
+    enum Reference {
+        Number(i64),
+        Prefix(&'static str, Box<Reference>),
+        Binary(usize, Box<Reference>, Box<Reference>),
+        Grouping(Box<Reference>),
+    }
+
+    impl Reference {
+        /// The integer result, or `None` if evaluating fails or the result is not an integer.
+        fn evaluate(&self) -> Option<i64> {
+            match self {
+                Reference::Number(value) => Some(*value),
+                Reference::Grouping(inner) => inner.evaluate(),
+                Reference::Prefix("-", operand) => operand.evaluate()?.checked_neg(),
+                Reference::Prefix("~", operand) => Some(!operand.evaluate()?),
+                Reference::Prefix(_, operand) => operand.evaluate(),
+                Reference::Binary(index, left, right) => {
+                    let (left, right) = (left.evaluate()?, right.evaluate()?);
+                    match REFERENCE_OPERATORS[*index].0 {
+                        "|" => Some(left | right),
+                        "xor" => Some(left ^ right),
+                        "&" => Some(left & right),
+                        "<<" => left.checked_shl(u32::try_from(right).ok()?),
+                        ">>" => left.checked_shr(u32::try_from(right).ok()?),
+                        "+" => left.checked_add(right),
+                        "-" => left.checked_sub(right),
+                        "*" => left.checked_mul(right),
+                        "/" if left.checked_rem(right)? == 0 => left.checked_div(right),
+                        "/" => None,
+                        "//" => left.checked_div(right),
+                        "%" if right == 0 => None,
+                        "%" => Some(left.wrapping_rem(right)),
+                        _ => match left {
+                            0 if right < 0 => None,
+                            0 => Some(if right == 0 { 1 } else { 0 }),
+                            1 => Some(1),
+                            -1 => Some(if right % 2 == 0 { 1 } else { -1 }),
+                            _ => left.checked_pow(u32::try_from(right).ok()?),
+                        },
+                    }
+                }
+            }
+        }
+
+        fn precedence(&self) -> u8 {
+            match self {
+                Reference::Number(_) | Reference::Grouping(_) => u8::MAX,
+                Reference::Prefix(..) => REFERENCE_PREFIX_PRECEDENCE,
+                Reference::Binary(index, ..) => REFERENCE_OPERATORS[*index].1,
+            }
+        }
+
+        /// Writes the expression with only the parenthesis the reference precedences require, besides the explicit
+        /// groupings.
+        fn render(&self) -> String {
+            match self {
+                Reference::Number(value) => value.to_string(),
+                Reference::Grouping(inner) => format!("({})", inner.render()),
+                Reference::Prefix(operator, operand) => {
+                    format!("{}{}", operator, operand.render_operand(REFERENCE_PREFIX_PRECEDENCE))
+                }
+                Reference::Binary(index, left, right) => {
+                    let (symbol, precedence, right_associative) = REFERENCE_OPERATORS[*index];
+                    let left = left.render_operand(precedence + u8::from(right_associative));
+                    // a prefix operator on the right extends as far as the operator before it allows
+                    let right = match right.as_ref() {
+                        Reference::Prefix(..) => right.render(),
+                        _ => right.render_operand(precedence + u8::from(!right_associative)),
+                    };
+                    format!("{} {} {}", left, symbol, right)
+                }
+            }
+        }
+
+        fn render_operand(&self, minimum_precedence: u8) -> String {
+            if self.precedence() < minimum_precedence {
+                format!("({})", self.render())
+            } else {
+                self.render()
+            }
+        }
+    }
+
+    fn reference_expression() -> impl Strategy<Value = Reference> {
+        let number = prop_oneof![
+            // mostly small numbers, so chains of ^ or << rarely overflow
+            6 => (0i64..=3).prop_map(Reference::Number),
+            3 => (4i64..=100).prop_map(Reference::Number),
+            1 => Just(Reference::Number(i64::MAX)),
+        ];
+        number.prop_recursive(6, 48, 2, |inner| prop_oneof![
+            1 => (prop::sample::select(vec!["-", "~", "+"]), inner.clone())
+                .prop_map(|(operator, operand)| Reference::Prefix(operator, Box::new(operand))),
+            4 => (0..REFERENCE_OPERATORS.len(), inner.clone(), inner.clone())
+                .prop_map(|(index, left, right)| Reference::Binary(index, Box::new(left), Box::new(right))),
+            1 => inner.prop_map(|inner| Reference::Grouping(Box::new(inner))),
+        ])
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1024))]
+
+        #[test]
+        fn evaluation_matches_reference(expression in reference_expression()) {
+            let source = expression.render();
+            let result = evaluate_expression(&source);
+
+            match expression.evaluate() {
+                Some(expected) => prop_assert_eq!(result, Ok(Integer(expected)), "{}", source),
+                None => prop_assert!(!matches!(result, Ok(Integer(_))), "{} evaluated to {:?}", source, result),
+            }
+        }
+
+        #[test]
+        fn removing_a_parenthesis_unbalances(expression in reference_expression(), position in any::<prop::sample::Index>()) {
+            let source = expression.render();
+            let parenthesis: std::vec::Vec<usize> = source.match_indices(['(', ')']).map(|(index, _)| index).collect();
+            prop_assume!(!parenthesis.is_empty());
+
+            let mut unbalanced = source.clone();
+            unbalanced.remove(parenthesis[position.index(parenthesis.len())]);
+
+            prop_assert!(matches!(parse(&unbalanced), Err(EvalError::UnbalancedParenthesis { .. })), "{}", unbalanced);
+        }
+    }
+}
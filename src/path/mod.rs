@@ -0,0 +1,140 @@
+//!
+//! Paths of the variables used in an expression, e.g. `user.address.zip`, `items[0]` or `tags['env']`.
+//!
+//! A path starts with an identifier and continues with any number of member accesses and bracket indexes.
+//! Identifiers follow the Unicode XID rules and may also start with an underscore, so `sensor_1`, `maxTemp2`
+//! and `größe` are all valid.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+
+/// One access after the root identifier of a [VariablePath].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PathSegment {
+    /// `.name`
+    Field(Box<str>),
+    /// `[0]`
+    Index(usize),
+    /// `['name']`
+    Key(Box<str>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct VariablePath {
+    root: Box<str>,
+    segments: Vec<PathSegment>,
+}
+
+impl VariablePath {
+    pub fn new(root: &str) -> Self {
+        Self { root: Box::from(root), segments: Vec::new() }
+    }
+
+    pub fn field(mut self, name: &str) -> Self {
+        self.segments.push(PathSegment::Field(Box::from(name)));
+        self
+    }
+
+    pub fn index(mut self, index: usize) -> Self {
+        self.segments.push(PathSegment::Index(index));
+        self
+    }
+
+    pub fn key(mut self, key: &str) -> Self {
+        self.segments.push(PathSegment::Key(Box::from(key)));
+        self
+    }
+
+    pub(crate) fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// The identifier if the path consists of nothing else, e.g. `a` but not `a.b`.
+    pub fn as_identifier(&self) -> Option<&str> {
+        if self.segments.is_empty() {
+            Some(&self.root)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<&str> for VariablePath {
+    fn from(root: &str) -> Self {
+        Self::new(root)
+    }
+}
+
+/// Prints the path the way it is written in an expression.
+impl Display for VariablePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.root)?;
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Field(name) => write!(f, ".{}", name)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                PathSegment::Key(key) => {
+                    // only quotes and backslashes are escaped, so flat context keys like `tags['größe']` match
+                    write!(f, "['")?;
+                    for character in key.chars() {
+                        if matches!(character, '\'' | '\\') {
+                            write!(f, "\\")?;
+                        }
+                        write!(f, "{}", character)?;
+                    }
+                    write!(f, "']")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+
+pub fn is_identifier_start(character: char) -> bool {
+    character == '_' || unicode_xid::UnicodeXID::is_xid_start(character)
+}
+
+pub fn is_identifier_continue(character: char) -> bool {
+    unicode_xid::UnicodeXID::is_xid_continue(character)
+}
+
+
+// XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+    use crate::path::{is_identifier_continue, is_identifier_start, VariablePath};
+
+
+    #[test]
+    fn display_as_written() {
+        let path = VariablePath::new("user").field("address").index(0).key("it's");
+
+        assert_eq!(path.to_string(), "user.address[0]['it\\'s']");
+        assert_eq!(VariablePath::new("tags").key("größe \\ ü").to_string(), "tags['größe \\\\ ü']");
+        assert_eq!(path.as_identifier(), None);
+        assert_eq!(VariablePath::from("user").as_identifier(), Some("user"));
+    }
+
+    #[test]
+    fn identifier_characters() {
+        assert!(is_identifier_start('_'));
+        assert!(is_identifier_start('ä'));
+        assert!(!is_identifier_start('1'));
+        assert!(is_identifier_continue('1'));
+        assert!(!is_identifier_continue('.'));
+        assert!(!is_identifier_continue('€'));
+    }
+}
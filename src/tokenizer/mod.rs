@@ -3,9 +3,9 @@
 //!
 //! Since we are dealing with mathematical expressions we have to following flavours of tokens:
 //!
-//! literal -> 3 44 4.5
-//! variable -> a someName
-//! operator -> + - / * && ||
+//! literal -> 3 44 4.5 .5 1_000 6.02e23 0xff 0o17 0b1010 'text' "text" r'raw text' 19.99d (with the decimal feature)
+//! variable -> a someName sensor_1 user.address.zip items[0] tags['env']
+//! operator -> + - / * && || ??
 //!
 //! regex
 //! (?:[<>=!]=?|=|&&|\|\||\^|-?[0-9][0-9,\.]*|[A-z]*)
@@ -20,8 +20,11 @@ use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
 use core::iter::Peekable;
-use core::str::Chars;
-use crate::definition::{Operator, OperatorProperties, Parenthesis, Token};
+use core::str::CharIndices;
+use crate::definition::{Fixity, Keyword, Operator, Parenthesis, SpannedToken, Token};
+use crate::error::{EvalError, LexErrorKind, Span};
+use crate::options::LexMode;
+use crate::path::{is_identifier_continue, is_identifier_start, PathSegment, VariablePath};
 use crate::Literal;
 use crate::Literal::Boolean;
 
@@ -30,30 +33,47 @@ use crate::Literal::Boolean;
 
 
 
-pub const OR_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "||", precedence: 1, left_associative: false, operator: Operator::Or});
+pub const OR_OPERATOR: Token = Token::Operator(Operator::Or.properties());
 
-pub const AND_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "&&", precedence: 2, left_associative: false, operator: Operator::And });
+pub const AND_OPERATOR: Token = Token::Operator(Operator::And.properties());
 
-pub const NOT_EQUAL_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "!=", precedence: 3, left_associative: false, operator: Operator::NotEqual });
-pub const EQUAL_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "==", precedence: 3, left_associative: false, operator: Operator::Equal });
+pub const NOT_EQUAL_OPERATOR: Token = Token::Operator(Operator::NotEqual.properties());
+pub const EQUAL_OPERATOR: Token = Token::Operator(Operator::Equal.properties());
 
-pub const GREATER_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: ">", precedence: 4, left_associative: false, operator: Operator::Greater });
-pub const GREATER_OR_EQUAL_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: ">=", precedence: 4, left_associative: false, operator: Operator::GreaterOrEqual });
-pub const LESS_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "<", precedence: 4, left_associative: false, operator: Operator::Less });
-pub const LESS_OR_EQUAL_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "<=", precedence: 4, left_associative: false, operator: Operator::LessOrEqual });
+pub const IS_NULL_OPERATOR: Token = Token::Operator(Operator::IsNull.properties());
+pub const IS_NOT_NULL_OPERATOR: Token = Token::Operator(Operator::IsNotNull.properties());
 
+pub const GREATER_OPERATOR: Token = Token::Operator(Operator::Greater.properties());
+pub const GREATER_OR_EQUAL_OPERATOR: Token = Token::Operator(Operator::GreaterOrEqual.properties());
+pub const LESS_OPERATOR: Token = Token::Operator(Operator::Less.properties());
+pub const LESS_OR_EQUAL_OPERATOR: Token = Token::Operator(Operator::LessOrEqual.properties());
 
-pub const PLUS_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "+", precedence: 5, left_associative: false, operator: Operator::Plus });
-pub const MINUS_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "-", precedence: 5, left_associative: false, operator: Operator::Minus });
-pub const DIVIDE_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "/", precedence: 6, left_associative: false, operator: Operator::Divide });
-pub const MULTIPLY_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "*", precedence: 6, left_associative: false, operator: Operator::Multiply });
+pub const BIT_OR_OPERATOR: Token = Token::Operator(Operator::BitOr.properties());
+pub const BIT_XOR_OPERATOR: Token = Token::Operator(Operator::BitXor.properties());
+pub const BIT_AND_OPERATOR: Token = Token::Operator(Operator::BitAnd.properties());
+pub const SHIFT_LEFT_OPERATOR: Token = Token::Operator(Operator::ShiftLeft.properties());
+pub const SHIFT_RIGHT_OPERATOR: Token = Token::Operator(Operator::ShiftRight.properties());
 
-pub const POWER_OF_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "^", precedence: 7, left_associative: true, operator: Operator::PowerOf });
+pub const COALESCE_OPERATOR: Token = Token::Operator(Operator::Coalesce.properties());
 
-pub const NOT_OPERATOR: Token = Token::Operator(OperatorProperties { symbol: "!", precedence: 8, left_associative: false, operator: Operator::Not });
+pub const PLUS_OPERATOR: Token = Token::Operator(Operator::Plus.properties());
+pub const MINUS_OPERATOR: Token = Token::Operator(Operator::Minus.properties());
+
+pub const DIVIDE_OPERATOR: Token = Token::Operator(Operator::Divide.properties());
+pub const INTEGER_DIVIDE_OPERATOR: Token = Token::Operator(Operator::IntegerDivide.properties());
+pub const MULTIPLY_OPERATOR: Token = Token::Operator(Operator::Multiply.properties());
+pub const MODULO_OPERATOR: Token = Token::Operator(Operator::Modulo.properties());
+
+pub const NOT_OPERATOR: Token = Token::Operator(Operator::Not.properties());
+pub const BIT_NOT_OPERATOR: Token = Token::Operator(Operator::BitNot.properties());
+pub const UNARY_MINUS_OPERATOR: Token = Token::Operator(Operator::UnaryMinus.properties());
+pub const UNARY_PLUS_OPERATOR: Token = Token::Operator(Operator::UnaryPlus.properties());
+
+pub const POWER_OF_OPERATOR: Token = Token::Operator(Operator::PowerOf.properties());
 
 pub const TRUE: Token = Token::Literal(Boolean(true));
 pub const FALSE: Token = Token::Literal(Boolean(false));
+pub const NULL: Token = Token::Literal(Literal::Null);
 
 
 
@@ -61,186 +81,442 @@ pub const FALSE: Token = Token::Literal(Boolean(false));
 
 
 
-pub fn string_to_tokens(expression_string: &str) -> Result<Vec<Token>, &'static str>
+pub fn string_to_tokens(expression_string: &str) -> Result<Vec<SpannedToken>, EvalError>
+{
+    string_to_tokens_with_mode(expression_string, LexMode::Strict)
+}
+
+pub fn string_to_tokens_with_mode(expression_string: &str, mode: LexMode) -> Result<Vec<SpannedToken>, EvalError>
 {
-    let mut result: Vec<Option<Token>> = Vec::new();
-    let mut iter = expression_string.chars().peekable();
+    let mut result: Vec<SpannedToken> = Vec::new();
+    let mut iter = expression_string.char_indices().peekable();
 
 
-    while let Some(character) = iter.next() {
-        let operator: Option<Token> = match character {
+    while let Some((position, character)) = iter.next() {
+        let single_character_end = position + character.len_utf8();
+        let is_prefix_position = expects_operand(result.last());
+        let operator: Option<(Token, usize)> = match character {
             '\'' | '\"' => {
-                extract_string_literal(&mut iter, character)
+                Some(extract_string_literal(&mut iter, character, position, false)?)
             }
-            'A'..='Z' | 'a'..='z' => {
-                extract_variable(&mut iter, character)
+            'r' if matches!(iter.peek(), Some((_, '\'' | '\"'))) => {
+                let (_, quote) = iter.next().unwrap_or((position, '\''));
+                Some(extract_string_literal(&mut iter, quote, position, true)?)
             }
             '0'..='9' => {
-                extract_number(&mut iter, character)
+                Some(extract_number(&mut iter, character, position)?)
+            }
+            '.' if matches!(iter.peek(), Some((_, next)) if next.is_ascii_digit()) => {
+                Some(extract_number(&mut iter, character, position)?)
             }
             '|' => {
-                extract_operator(&mut iter, OR_OPERATOR, '|')
+                extract_operator_simple(&mut iter, BIT_OR_OPERATOR, OR_OPERATOR, '|', position)
             }
             '&' => {
-                extract_operator(&mut iter, AND_OPERATOR, '&')
+                extract_operator_simple(&mut iter, BIT_AND_OPERATOR, AND_OPERATOR, '&', position)
             }
             '=' => {
-                extract_operator(&mut iter, EQUAL_OPERATOR, '=')
+                extract_operator(&mut iter, EQUAL_OPERATOR, '=', position)
+            }
+            '?' => {
+                extract_operator_simple(&mut iter, Token::QuestionMark, COALESCE_OPERATOR, '?', position)
+            }
+            ':' => {
+                Some((Token::Colon, single_character_end))
             }
             '!' => {
-                extract_operator_simple(&mut iter, NOT_OPERATOR, NOT_EQUAL_OPERATOR, '=')
+                extract_operator_simple(&mut iter, NOT_OPERATOR, NOT_EQUAL_OPERATOR, '=', position)
             }
             '<' => {
-                extract_operator_simple(&mut iter, LESS_OPERATOR, LESS_OR_EQUAL_OPERATOR, '=')
+                extract_operator_longest(&mut iter, LESS_OPERATOR, [('=', LESS_OR_EQUAL_OPERATOR), ('<', SHIFT_LEFT_OPERATOR)], position)
             }
             '>' => {
-                extract_operator_simple(&mut iter, GREATER_OPERATOR, GREATER_OR_EQUAL_OPERATOR, '=')
+                extract_operator_longest(&mut iter, GREATER_OPERATOR, [('=', GREATER_OR_EQUAL_OPERATOR), ('>', SHIFT_RIGHT_OPERATOR)], position)
             }
             '^' => {
-                Some(POWER_OF_OPERATOR)
+                Some((POWER_OF_OPERATOR, single_character_end))
+            }
+            '+' if is_prefix_position => {
+                Some((UNARY_PLUS_OPERATOR, single_character_end))
             }
             '+' => {
-                Some(PLUS_OPERATOR)
+                Some((PLUS_OPERATOR, single_character_end))
+            }
+            '-' | '−' if is_prefix_position => {
+                Some((UNARY_MINUS_OPERATOR, single_character_end))
             }
             '-' | '−' => {
-                Some(MINUS_OPERATOR)
+                Some((MINUS_OPERATOR, single_character_end))
             }
             '*' | '×' => {
-                Some(MULTIPLY_OPERATOR)
+                Some((MULTIPLY_OPERATOR, single_character_end))
             }
-            '/' | '÷' => {
-                Some(DIVIDE_OPERATOR)
+            '/' => {
+                extract_operator_simple(&mut iter, DIVIDE_OPERATOR, INTEGER_DIVIDE_OPERATOR, '/', position)
+            }
+            '÷' => {
+                Some((DIVIDE_OPERATOR, single_character_end))
+            }
+            '%' => {
+                Some((MODULO_OPERATOR, single_character_end))
+            }
+            '~' => {
+                Some((BIT_NOT_OPERATOR, single_character_end))
             }
             ')' => {
-                Some(Token::Parenthesis(Parenthesis::RightParenthesis))
+                Some((Token::Parenthesis(Parenthesis::RightParenthesis), single_character_end))
             }
             '(' => {
-                Some(Token::Parenthesis(Parenthesis::LeftParenthesis))
+                Some((Token::Parenthesis(Parenthesis::LeftParenthesis), single_character_end))
+            }
+            ',' => {
+                Some((Token::Comma, single_character_end))
+            }
+            _ if is_identifier_start(character) => {
+                Some(extract_variable(&mut iter, character, position)?)
             }
             _ => {
                 None
             }
         };
-        result.push(operator);
+        match operator {
+            Some((token, end)) => {
+                result.push(SpannedToken::new(token, Span::new(position, end)));
+            }
+            None if mode == LexMode::Strict && !character.is_whitespace() => {
+                let span = Span::new(position, single_character_end);
+                return Err(EvalError::Lex { kind: LexErrorKind::UnexpectedCharacter(character), span });
+            }
+            None => {}
+        }
+    }
+    Ok(result)
+}
+
+/// An operand is expected at the start of the expression, after an operator other than a postfix one, an opening
+/// parenthesis, a comma or a part of a conditional. Operators found in this position are prefix operators.
+fn expects_operand(previous: Option<&SpannedToken>) -> bool {
+    match previous.map(|spanned| &spanned.token) {
+        None => true,
+        Some(Token::Operator(properties)) => properties.fixity != Fixity::Postfix,
+        Some(Token::Parenthesis(Parenthesis::LeftParenthesis)) => true,
+        Some(Token::Comma) => true,
+        Some(Token::QuestionMark | Token::Colon | Token::Keyword(_)) => true,
+        Some(_) => false,
     }
-    let filtered_result = result.into_iter().filter_map(|e| e).collect();
-    return Ok(filtered_result);
 }
 
-fn extract_number(expression_string_iterator: &mut Peekable<Chars>, character: char) -> Option<Token> {
-    let mut is_integer = true;
+/// Reads a decimal number like `42`, `1_000`, `.5` or `6.02e-23`, or an integer with a `0x`, `0o` or `0b` prefix.
+fn extract_number(expression_string_iterator: &mut Peekable<CharIndices>, character: char, start: usize) -> Result<(Token, usize), EvalError> {
     let mut number_string = String::new();
     number_string.push(character);
 
-    while let Some(character) = expression_string_iterator.next() {
-        match character {
-            '0'..='9' => {
-                number_string.push(character);
-            }
-            '.' => {
-                is_integer = false;
-                number_string.push(character);
-            }
-            _ => {
-                break;
-            }
+    if character == '0' {
+        if let Some((_, prefix)) = expression_string_iterator.next_if(|(_, next)| matches!(next, 'x' | 'X' | 'o' | 'O' | 'b' | 'B')) {
+            number_string.push(prefix);
+            return extract_radix_integer(expression_string_iterator, number_string, start);
         }
     }
 
+    let mut is_integer = character != '.';
+    let mut is_valid = extract_digits(expression_string_iterator, &mut number_string, 10, if is_integer { 0 } else { 1 });
+
+    if is_integer {
+        if let Some((_, separator)) = expression_string_iterator.next_if(|(_, next)| *next == '.') {
+            number_string.push(separator);
+            is_integer = false;
+            let fraction = number_string.len();
+            is_valid &= extract_digits(expression_string_iterator, &mut number_string, 10, fraction);
+        }
+    }
+    if let Some((_, exponent)) = expression_string_iterator.next_if(|(_, next)| *next == 'e' || *next == 'E') {
+        number_string.push(exponent);
+        if let Some((_, sign)) = expression_string_iterator.next_if(|(_, next)| *next == '+' || *next == '-') {
+            number_string.push(sign);
+        }
+        is_integer = false;
+        let exponent_digits = number_string.len();
+        is_valid &= extract_digits(expression_string_iterator, &mut number_string, 10, exponent_digits);
+    }
+    #[cfg(feature = "decimal")]
+    let is_money = expression_string_iterator.next_if(|(_, next)| *next == 'd')
+        .map(|(_, suffix)| number_string.push(suffix))
+        .is_some();
+    is_valid &= !extract_trailing_garbage(expression_string_iterator, &mut number_string);
+
+    let end = start + number_string.len();
+    let error = |kind: LexErrorKind| EvalError::Lex { kind, span: Span::new(start, end) };
+    if !is_valid {
+        return Err(error(LexErrorKind::InvalidNumber));
+    }
+
+    let digits = number_string.replace('_', "");
+    #[cfg(feature = "decimal")]
+    if is_money {
+        return extract_money(digits.trim_end_matches('d')).map(|value| (Token::Literal(Literal::Money(value)), end)).map_err(error);
+    }
     let literal = if is_integer {
-        let result = number_string.parse::<i64>().unwrap();
-        Literal::Integer(result)
+        digits.parse::<i64>().map(Literal::Integer).map_err(|_| error(LexErrorKind::NumberOutOfRange))?
     } else {
-        let result = number_string.parse::<f64>().unwrap();
-        Literal::Decimal(result)
+        match digits.parse::<f64>() {
+            Ok(value) if value.is_finite() => Literal::Decimal(value),
+            Ok(_) => return Err(error(LexErrorKind::NumberOutOfRange)),
+            Err(_) => return Err(error(LexErrorKind::InvalidNumber)),
+        }
     };
+    Ok((Token::Literal(literal), end))
+}
 
-    return Some(Token::Literal(literal));
+/// Converts the digits of a money literal like `19.99d` or `1.5e3d` without rounding them.
+#[cfg(feature = "decimal")]
+fn extract_money(digits: &str) -> Result<rust_decimal::Decimal, LexErrorKind> {
+    let value = match digits.contains(['e', 'E']) {
+        true => rust_decimal::Decimal::from_scientific(digits),
+        false => rust_decimal::Decimal::from_str_exact(digits),
+    };
+    value.map_err(|_| LexErrorKind::NumberOutOfRange)
 }
 
-fn extract_string_literal(expression_string_iterator: &mut Peekable<Chars>, character: char) -> Option<Token> {
-    let mut token_string = String::new();
+/// Reads the digits of an integer after its `0x`, `0o` or `0b` prefix.
+fn extract_radix_integer(expression_string_iterator: &mut Peekable<CharIndices>, mut number_string: String, start: usize) -> Result<(Token, usize), EvalError> {
+    let radix = match &number_string[1..] {
+        "x" | "X" => 16,
+        "o" | "O" => 8,
+        _ => 2,
+    };
+    let is_valid = extract_digits(expression_string_iterator, &mut number_string, radix, 2)
+        & !extract_trailing_garbage(expression_string_iterator, &mut number_string);
 
-    while let Some(character) = expression_string_iterator.next() {
-        match character {
-            'A'..='Z' | 'a'..='z' => {
-                token_string.push(character);
-            }
-            _ => {
-                break;
-            }
-        }
+    let end = start + number_string.len();
+    if !is_valid {
+        return Err(EvalError::Lex { kind: LexErrorKind::InvalidNumber, span: Span::new(start, end) });
     }
+    i64::from_str_radix(&number_string[2..].replace('_', ""), radix)
+        .map(|value| (Token::Literal(Literal::Integer(value)), end))
+        .map_err(|_| EvalError::Lex { kind: LexErrorKind::NumberOutOfRange, span: Span::new(start, end) })
+}
 
-    let mut token = Token::Literal(Literal::String(Box::from(token_string.clone())));
+/// Appends digits of the given radix and `_` separators. Returns whether the digits starting at `from` are
+/// well formed, i.e. not empty and every separator is placed between two digits.
+fn extract_digits(expression_string_iterator: &mut Peekable<CharIndices>, number_string: &mut String, radix: u32, from: usize) -> bool {
+    while let Some((_, digit)) = expression_string_iterator.next_if(|(_, next)| next.is_digit(radix) || *next == '_') {
+        number_string.push(digit);
+    }
+    let digits = &number_string[from..];
+    !digits.is_empty() && !digits.starts_with('_') && !digits.ends_with('_') && !digits.contains("__")
+}
 
-    return Some(token);
+/// Letters, digits or dots directly after a number, e.g. in `12abc`, `1.2.3` or `0b102`, make it malformed.
+fn extract_trailing_garbage(expression_string_iterator: &mut Peekable<CharIndices>, number_string: &mut String) -> bool {
+    let mut found = false;
+    while let Some((_, character)) = expression_string_iterator.next_if(|(_, next)| is_identifier_continue(*next) || *next == '.') {
+        number_string.push(character);
+        found = true;
+    }
+    found
 }
 
-fn extract_variable(expression_string_iterator: &mut Peekable<Chars>, character: char) -> Option<Token> {
+/// Reads a string literal up to the closing `quote`, the opening quote is already consumed.
+/// Raw literals keep backslashes as they are, all others resolve the escape sequences.
+fn extract_string_literal(expression_string_iterator: &mut Peekable<CharIndices>, quote: char, start: usize, raw: bool) -> Result<(Token, usize), EvalError> {
     let mut token_string = String::new();
-    token_string.push(character);
+    let mut end = start;
 
-    while let Some(character) = expression_string_iterator.next() {
+    while let Some((position, character)) = expression_string_iterator.next() {
+        end = position + character.len_utf8();
         match character {
-            'A'..='Z' | 'a'..='z' => {
-                token_string.push(character);
+            _ if character == quote => {
+                return Ok((Token::Literal(Literal::String(Box::from(token_string))), end));
+            }
+            '\\' if !raw => {
+                let (escaped, escape_end) = extract_escape(expression_string_iterator, position)?;
+                token_string.push(escaped);
+                end = escape_end;
             }
             _ => {
-                break;
+                token_string.push(character);
             }
         }
     }
 
-    let mut token = Token::Variable(Box::from(token_string.clone()));
+    Err(EvalError::Lex { kind: LexErrorKind::UnterminatedString, span: Span::new(start, end.max(start + 1)) })
+}
+
+/// Resolves the escape sequence after the backslash at `start`, e.g. `\n` or `\u{1F600}`.
+fn extract_escape(expression_string_iterator: &mut Peekable<CharIndices>, start: usize) -> Result<(char, usize), EvalError> {
+    let invalid = |end: usize| EvalError::Lex { kind: LexErrorKind::InvalidEscape, span: Span::new(start, end) };
+
+    let (position, character) = expression_string_iterator.next().ok_or(invalid(start + 1))?;
+    let end = position + character.len_utf8();
+    match character {
+        'n' => Ok(('\n', end)),
+        't' => Ok(('\t', end)),
+        '\\' | '\'' | '\"' => Ok((character, end)),
+        'u' => {
+            expression_string_iterator.next_if(|(_, next)| *next == '{').ok_or(invalid(end))?;
+            let mut code = String::new();
+            while let Some((_, digit)) = expression_string_iterator.next_if(|(_, next)| next.is_ascii_hexdigit()) {
+                code.push(digit);
+            }
+            let end = end + 1 + code.len();
+            let (closing, _) = expression_string_iterator.next_if(|(_, next)| *next == '}').ok_or(invalid(end))?;
+
+            let end = closing + 1;
+            if code.is_empty() || code.len() > 6 {
+                return Err(invalid(end));
+            }
+            u32::from_str_radix(&code, 16).ok()
+                .and_then(char::from_u32)
+                .map(|escaped| (escaped, end))
+                .ok_or(invalid(end))
+        }
+        _ => Err(invalid(end)),
+    }
+}
 
+/// Reads an identifier and the member accesses and bracket indexes directly following it, e.g. `items[0].name`.
+fn extract_variable(expression_string_iterator: &mut Peekable<CharIndices>, character: char, start: usize) -> Result<(Token, usize), EvalError> {
+    let root = extract_identifier(expression_string_iterator, character);
+    let mut end = start + root.len();
 
     //TODO move this out of here
-    if token_string == "true" {
-        token = TRUE
+    match root.as_str() {
+        "true" => return Ok((TRUE, end)),
+        "false" => return Ok((FALSE, end)),
+        "null" => return Ok((NULL, end)),
+        "xor" => return Ok((BIT_XOR_OPERATOR, end)),
+        "is" => return extract_null_test(expression_string_iterator, start, end),
+        "if" => return Ok((Token::Keyword(Keyword::If), end)),
+        "then" => return Ok((Token::Keyword(Keyword::Then), end)),
+        "else" => return Ok((Token::Keyword(Keyword::Else), end)),
+        _ => {}
+    }
+
+    let mut path = VariablePath::new(&root);
+    while let Some((position, separator)) = expression_string_iterator.next_if(|(_, next)| *next == '.' || *next == '[') {
+        let invalid = |end: usize| EvalError::Lex { kind: LexErrorKind::InvalidPath, span: Span::new(position, end) };
+        let (segment, segment_end) = match separator {
+            '.' => {
+                let (_, first) = expression_string_iterator.next_if(|(_, next)| is_identifier_start(*next))
+                    .ok_or(invalid(position + 1))?;
+                let name = extract_identifier(expression_string_iterator, first);
+                let segment_end = position + 1 + name.len();
+                (PathSegment::Field(Box::from(name)), segment_end)
+            }
+            _ => {
+                let (segment, inner_end) = extract_index(expression_string_iterator, position)?;
+                let (closing, _) = expression_string_iterator.next_if(|(_, next)| *next == ']')
+                    .ok_or(invalid(inner_end))?;
+                (segment, closing + 1)
+            }
+        };
+        path.push(segment);
+        end = segment_end;
     }
-    if token_string == "false" {
-        token = FALSE
+
+    Ok((Token::Variable(path), end))
+}
+
+/// Reads the rest of `is null` or `is not null`, `is` ending at `end` is already consumed.
+fn extract_null_test(expression_string_iterator: &mut Peekable<CharIndices>, start: usize, end: usize) -> Result<(Token, usize), EvalError> {
+    let invalid = |end: usize| EvalError::Lex { kind: LexErrorKind::InvalidNullTest, span: Span::new(start, end) };
+
+    match extract_keyword(expression_string_iterator) {
+        Some((keyword, keyword_end)) if keyword == "null" => Ok((IS_NULL_OPERATOR, keyword_end)),
+        Some((keyword, keyword_end)) if keyword == "not" => match extract_keyword(expression_string_iterator) {
+            Some((keyword, null_end)) if keyword == "null" => Ok((IS_NOT_NULL_OPERATOR, null_end)),
+            Some((_, other_end)) => Err(invalid(other_end)),
+            None => Err(invalid(keyword_end)),
+        },
+        Some((_, other_end)) => Err(invalid(other_end)),
+        None => Err(invalid(end)),
     }
+}
+
+/// Skips whitespace and reads the identifier following it, if there is one.
+fn extract_keyword(expression_string_iterator: &mut Peekable<CharIndices>) -> Option<(String, usize)> {
+    while expression_string_iterator.next_if(|(_, next)| next.is_whitespace()).is_some() {}
 
-    return Some(token);
+    let (position, character) = expression_string_iterator.next_if(|(_, next)| is_identifier_start(*next))?;
+    let keyword = extract_identifier(expression_string_iterator, character);
+    let end = position + keyword.len();
+    Some((keyword, end))
 }
 
-fn extract_operator_simple(expression_string_iterator: &mut Peekable<Chars>, operator: Token, second_operator: Token, expected: char) -> Option<Token> {
-    let next_char = expression_string_iterator.peek();
-    return match next_char {
-        Some(value) => {
-            if *value == expected {
-                return Some(second_operator);
-            }
-            return Some(operator);
-        }
-        None => { Some(operator) }
-    };
+fn extract_identifier(expression_string_iterator: &mut Peekable<CharIndices>, character: char) -> String {
+    let mut identifier = String::new();
+    identifier.push(character);
 
+    while let Some((_, character)) = expression_string_iterator.next_if(|(_, next)| is_identifier_continue(*next)) {
+        identifier.push(character);
+    }
+    identifier
 }
 
+/// Reads the index or the quoted key inside of brackets, the opening bracket at `start` is already consumed.
+fn extract_index(expression_string_iterator: &mut Peekable<CharIndices>, start: usize) -> Result<(PathSegment, usize), EvalError> {
+    let invalid = |end: usize| EvalError::Lex { kind: LexErrorKind::InvalidPath, span: Span::new(start, end) };
 
-fn extract_operator(expression_string_iterator: &mut Peekable<Chars>, operator: Token, expected: char) -> Option<Token> {
-    match expression_string_iterator.peek() {
-        Some(value) => {
-            if *value == expected {
-                return Some(operator);
+    match expression_string_iterator.next_if(|(_, next)| next.is_ascii_digit() || *next == '\'' || *next == '"') {
+        Some((position, character)) if character.is_ascii_digit() => {
+            let mut digits = String::new();
+            digits.push(character);
+            while let Some((_, digit)) = expression_string_iterator.next_if(|(_, next)| next.is_ascii_digit()) {
+                digits.push(digit);
+            }
+            let end = position + digits.len();
+            digits.parse::<usize>()
+                .map(|index| (PathSegment::Index(index), end))
+                .map_err(|_| invalid(end))
+        }
+        Some((position, quote)) => {
+            match extract_string_literal(expression_string_iterator, quote, position, false)? {
+                (Token::Literal(Literal::String(key)), end) => Ok((PathSegment::Key(key), end)),
+                (_, end) => Err(invalid(end)),
             }
-            return None;
         }
-        None => { return None; }
+        None => Err(invalid(start + 1)),
+    }
+}
+
+fn extract_operator_simple(expression_string_iterator: &mut Peekable<CharIndices>, operator: Token, second_operator: Token, expected: char, start: usize) -> Option<(Token, usize)> {
+    match expression_string_iterator.next_if(|(_, value)| *value == expected) {
+        Some(_) => Some((second_operator, start + 2)),
+        None => Some((operator, start + 1)),
+    }
+}
+
+/// Reads an operator which is either a single character or continues with one of the characters in `longer`,
+/// e.g. `<`, `<=` or `<<`.
+fn extract_operator_longest<const N: usize>(expression_string_iterator: &mut Peekable<CharIndices>, operator: Token, longer: [(char, Token); N], start: usize) -> Option<(Token, usize)> {
+    let next = expression_string_iterator.peek().map(|(_, next)| *next);
+    match longer.into_iter().find(|(expected, _)| Some(*expected) == next) {
+        Some((_, longer_operator)) => {
+            expression_string_iterator.next();
+            Some((longer_operator, start + 2))
+        }
+        None => Some((operator, start + 1)),
     }
 }
 
 
+fn extract_operator(expression_string_iterator: &mut Peekable<CharIndices>, operator: Token, expected: char, start: usize) -> Option<(Token, usize)> {
+    expression_string_iterator.next_if(|(_, value)| *value == expected)
+        .map(|_| (operator, start + 2))
+}
+
+
 // XXXXXXXXXXXXXXXXXX TESTS XXXXXXXXXXXXXXXXXX
 
 #[cfg(test)]
 mod tests {
     use std::prelude::v1::{Box, Vec};
-    use crate::tokenizer::{AND_OPERATOR, Literal, Operator, string_to_tokens, Token};
+    use crate::definition::{Keyword, SpannedToken};
+    use crate::path::VariablePath;
+    use crate::tokenizer::{AND_OPERATOR, BIT_AND_OPERATOR, BIT_OR_OPERATOR, COALESCE_OPERATOR, EQUAL_OPERATOR, GREATER_OPERATOR, IS_NOT_NULL_OPERATOR, IS_NULL_OPERATOR, LESS_OR_EQUAL_OPERATOR, Literal, Operator, OR_OPERATOR, PLUS_OPERATOR, SHIFT_LEFT_OPERATOR, SHIFT_RIGHT_OPERATOR, string_to_tokens, string_to_tokens_with_mode, Token};
+    use crate::options::LexMode;
     use rstest::rstest;
+    use crate::error::{EvalError, LexErrorKind, Span};
     use crate::Literal::Boolean;
 
 
@@ -250,24 +526,88 @@ mod tests {
         let a = "\"A\"";
         let vec = string_to_tokens(a).unwrap();
         let result = vec.first().unwrap();
-        assert_eq!(*result, Token::Literal(Literal::String(Box::from("A"))));
+        assert_eq!(result.token, Token::Literal(Literal::String(Box::from("A"))));
 
         let b = "\'B\'";
         let vec = string_to_tokens(b).unwrap();
         let result = vec.first().unwrap();
-        assert_eq!(*result, Token::Literal(Literal::String(Box::from("B"))));
+        assert_eq!(result.token, Token::Literal(Literal::String(Box::from("B"))));
 
         let c = "somevar == 'C'";
         let vec = string_to_tokens(c).unwrap();
         let result = vec.get(2).unwrap();
-        assert_eq!(*result, Token::Literal(Literal::String(Box::from("C"))));
+        assert_eq!(result.token, Token::Literal(Literal::String(Box::from("C"))));
 
         let d = "somevar == 'D'";
         let vec = string_to_tokens(d).unwrap();
         let result = vec.get(2).unwrap();
-        assert_eq!(*result, Token::Literal(Literal::String(Box::from("D"))));
+        assert_eq!(result.token, Token::Literal(Literal::String(Box::from("D"))));
+
+
+    }
+
+    #[rstest]
+    #[case::spaces("'hello world'", "hello world")]
+    #[case::digits("\"A1\"", "A1")]
+    #[case::unicode("'ÄÖÜ €'", "ÄÖÜ €")]
+    #[case::empty("''", "")]
+    #[case::other_quote_inside("\"it's\"", "it's")]
+    #[case::escaped_quote(r"'it\'s'", "it's")]
+    #[case::escaped_double_quote(r#""say \"hi\"""#, "say \"hi\"")]
+    #[case::newline_and_tab(r"'a\nb\tc'", "a\nb\tc")]
+    #[case::backslash(r"'back\\slash'", "back\\slash")]
+    #[case::unicode_escape(r"'\u{e9}\u{1F600}'", "é😀")]
+    #[case::raw(r"r'C:\new\table'", "C:\\new\\table")]
+    #[case::raw_double_quoted(r#"r"it's""#, "it's")]
+    #[case::raw_backslash_before_quote(r"r'abc\'", "abc\\")]
+    fn string_literals(#[case] expression: &str, #[case] expected: &str) {
+        let tokens = string_to_tokens(expression).unwrap();
+
+        assert_eq!(tokens, Vec::from([SpannedToken::new(Token::Literal(Literal::String(Box::from(expected))), Span::new(0, expression.len()))]));
+    }
 
+    #[rstest]
+    #[case::unterminated("x == 'abc", LexErrorKind::UnterminatedString, Span::new(5, 9))]
+    #[case::mismatched_quote("\"abc'", LexErrorKind::UnterminatedString, Span::new(0, 5))]
+    #[case::lone_quote("'", LexErrorKind::UnterminatedString, Span::new(0, 1))]
+    #[case::unterminated_raw("r'abc", LexErrorKind::UnterminatedString, Span::new(0, 5))]
+    #[case::unknown_escape(r"'a\qb'", LexErrorKind::InvalidEscape, Span::new(2, 4))]
+    #[case::trailing_backslash(r"'a\", LexErrorKind::InvalidEscape, Span::new(2, 3))]
+    #[case::unicode_without_braces(r"'\u00e9'", LexErrorKind::InvalidEscape, Span::new(1, 3))]
+    #[case::unicode_unclosed(r"'\u{e9'", LexErrorKind::InvalidEscape, Span::new(1, 6))]
+    #[case::unicode_empty(r"'\u{}'", LexErrorKind::InvalidEscape, Span::new(1, 5))]
+    #[case::unicode_out_of_range(r"'\u{110000}'", LexErrorKind::InvalidEscape, Span::new(1, 11))]
+    #[case::unicode_surrogate(r"'\u{D800}'", LexErrorKind::InvalidEscape, Span::new(1, 9))]
+    fn string_literal_errors(#[case] expression: &str, #[case] kind: LexErrorKind, #[case] span: Span) {
+        assert_eq!(string_to_tokens(expression), Err(EvalError::Lex { kind, span }));
+    }
 
+    #[rstest]
+    #[case::underscore_and_digit("sensor_1", VariablePath::new("sensor_1"))]
+    #[case::camel_case_with_digit("maxTemp2", VariablePath::new("maxTemp2"))]
+    #[case::leading_underscore("_private", VariablePath::new("_private"))]
+    #[case::unicode("größe", VariablePath::new("größe"))]
+    #[case::dotted("user.address.zip", VariablePath::new("user").field("address").field("zip"))]
+    #[case::index("items[0]", VariablePath::new("items").index(0))]
+    #[case::key("tags['env']", VariablePath::new("tags").key("env"))]
+    #[case::double_quoted_key("tags[\"a.b\"]", VariablePath::new("tags").key("a.b"))]
+    #[case::mixed("orders[12].lines[0]['sku']", VariablePath::new("orders").index(12).field("lines").index(0).key("sku"))]
+    fn variable_paths(#[case] expression: &str, #[case] expected: VariablePath) {
+        let tokens = string_to_tokens(expression).unwrap();
+
+        assert_eq!(tokens, Vec::from([SpannedToken::new(Token::Variable(expected), Span::new(0, expression.len()))]));
+    }
+
+    #[rstest]
+    #[case::trailing_dot("a. + 1", Span::new(1, 2))]
+    #[case::field_starting_with_digit("a.1", Span::new(1, 2))]
+    #[case::unclosed_bracket("a[0", Span::new(1, 3))]
+    #[case::empty_bracket("a[]", Span::new(1, 2))]
+    #[case::variable_index("a[i]", Span::new(1, 2))]
+    #[case::negative_index("a[-1]", Span::new(1, 2))]
+    #[case::index_overflow("a[99999999999999999999999]", Span::new(1, 25))]
+    fn invalid_variable_paths(#[case] expression: &str, #[case] span: Span) {
+        assert_eq!(string_to_tokens(expression), Err(EvalError::Lex { kind: LexErrorKind::InvalidPath, span }));
     }
 
     #[test]
@@ -275,7 +615,7 @@ mod tests {
         let x = "false";
         let vec = string_to_tokens(x).unwrap();
         let result = vec.first().unwrap();
-        assert_eq!(*result, Token::Literal(Literal::Boolean(false)));
+        assert_eq!(result.token, Token::Literal(Literal::Boolean(false)));
     }
 
     #[test]
@@ -283,16 +623,16 @@ mod tests {
         let x = "3";
         let vec = string_to_tokens(x).unwrap();
         let result = vec.first().unwrap();
-        assert_eq!(*result, Token::Literal(Literal::Integer(3)));
+        assert_eq!(result.token, Token::Literal(Literal::Integer(3)));
     }
 
     #[test]
     fn simple_less_operator_expression() {
         let x = "<=";
         let vec = string_to_tokens(x).unwrap();
-        let result = vec.get(0).unwrap();
+        let result = vec.first().unwrap();
 
-        assert_matches!(result, Token::Operator(properties) => {
+        assert_matches!(&result.token, Token::Operator(properties) => {
             assert_eq!(properties.operator, Operator::LessOrEqual);
         });
     }
@@ -301,7 +641,7 @@ mod tests {
     fn simple_boolean() {
 
         let x = "true && false";
-        let result = string_to_tokens(x).unwrap();
+        let result: Vec<Token> = string_to_tokens(x).unwrap().into_iter().map(|spanned| spanned.token).collect();
         let expected = Vec::from([Token::Literal(Boolean(true)), AND_OPERATOR, Token::Literal(Boolean(false))]);
 
         assert_eq!(result, expected);
@@ -317,18 +657,232 @@ mod tests {
     #[case::greater_or_equal(">=", Operator::GreaterOrEqual)]
     #[case::less("<", Operator::Less)]
     #[case::less_or_equal("<=", Operator::LessOrEqual)]
-    #[case::plus("+", Operator::Plus)]
-    #[case::minus("-", Operator::Minus)]
+    #[case::unary_plus("+", Operator::UnaryPlus)]
+    #[case::unary_minus("-", Operator::UnaryMinus)]
     #[case::divide("/", Operator::Divide)]
     #[case::multiply("*", Operator::Multiply)]
     #[case::power_of("^", Operator::PowerOf)]
+    #[case::coalesce("??", Operator::Coalesce)]
+    #[case::modulo("%", Operator::Modulo)]
+    #[case::integer_divide("//", Operator::IntegerDivide)]
+    #[case::bit_and("&", Operator::BitAnd)]
+    #[case::bit_or("|", Operator::BitOr)]
+    #[case::bit_xor("xor", Operator::BitXor)]
+    #[case::bit_not("~", Operator::BitNot)]
+    #[case::shift_left("<<", Operator::ShiftLeft)]
+    #[case::shift_right(">>", Operator::ShiftRight)]
     fn simple_parametrized_operator_expression(#[case] expression: &str,#[case] expected: Operator) {
         let vec = string_to_tokens(expression).unwrap();
-        let result = vec.get(0).unwrap();
+        let result = vec.first().unwrap();
 
-        assert_matches!(result, Token::Operator(properties) => {
+        assert_matches!(&result.token, Token::Operator(properties) => {
             assert_eq!(properties.operator, expected);
         });
     }
 
-}
\ No newline at end of file
+    #[rstest]
+    #[case::plus("1 + 2", Operator::Plus)]
+    #[case::minus("1 - 2", Operator::Minus)]
+    #[case::minus_after_parenthesis("(1) - 2", Operator::Minus)]
+    #[case::minus_after_variable("a -2", Operator::Minus)]
+    #[case::unary_minus_after_operator("1 * -2", Operator::UnaryMinus)]
+    #[case::unary_minus_after_parenthesis("(-2)", Operator::UnaryMinus)]
+    #[case::unary_minus_in_conditional("a ? -1 : 2", Operator::UnaryMinus)]
+    #[case::unary_minus_after_keyword("if a then 1 else -2", Operator::UnaryMinus)]
+    #[case::minus_after_null_test("a is null -2", Operator::Minus)]
+    fn prefix_and_infix_position(#[case] expression: &str, #[case] expected: Operator) {
+        let vec = string_to_tokens(expression).unwrap();
+        let operator = vec.iter().find_map(|spanned| match &spanned.token {
+            Token::Operator(properties) if properties.symbol == "-" || properties.symbol == "+" => Some(properties.operator),
+            _ => None,
+        });
+
+        assert_eq!(operator, Some(expected));
+    }
+
+    #[rstest]
+    #[case::integer("42", Literal::Integer(42))]
+    #[case::decimal("4.25", Literal::Decimal(4.25))]
+    #[case::leading_dot(".5", Literal::Decimal(0.5))]
+    #[case::separators("1_000_000", Literal::Integer(1_000_000))]
+    #[case::decimal_separators("1_000.000_1", Literal::Decimal(1_000.000_1))]
+    #[case::exponent("1e-9", Literal::Decimal(1e-9))]
+    #[case::upper_exponent("6.02E23", Literal::Decimal(6.02E23))]
+    #[case::positive_exponent("2e+3", Literal::Decimal(2000.0))]
+    #[case::hex("0xff", Literal::Integer(255))]
+    #[case::upper_hex("0XFF_FF", Literal::Integer(0xffff))]
+    #[case::octal("0o17", Literal::Integer(15))]
+    #[case::binary("0b1010", Literal::Integer(10))]
+    #[case::maximum("9223372036854775807", Literal::Integer(i64::MAX))]
+    #[case::maximum_hex("0x7fff_ffff_ffff_ffff", Literal::Integer(i64::MAX))]
+    fn number_literals(#[case] expression: &str, #[case] expected: Literal) {
+        let tokens = string_to_tokens(expression).unwrap();
+
+        assert_eq!(tokens, Vec::from([SpannedToken::new(Token::Literal(expected), Span::new(0, expression.len()))]));
+    }
+
+    #[rstest]
+    #[case::two_dots("1.2.3", LexErrorKind::InvalidNumber, Span::new(0, 5))]
+    #[case::trailing_dot("1. + 2", LexErrorKind::InvalidNumber, Span::new(0, 2))]
+    #[case::letters("12abc", LexErrorKind::InvalidNumber, Span::new(0, 5))]
+    #[case::empty_exponent("1e", LexErrorKind::InvalidNumber, Span::new(0, 2))]
+    #[case::signed_empty_exponent("1e+ 2", LexErrorKind::InvalidNumber, Span::new(0, 3))]
+    #[case::trailing_separator("1_", LexErrorKind::InvalidNumber, Span::new(0, 2))]
+    #[case::double_separator("1__0", LexErrorKind::InvalidNumber, Span::new(0, 4))]
+    #[case::separator_before_dot("1_.5", LexErrorKind::InvalidNumber, Span::new(0, 4))]
+    #[case::empty_hex("0x", LexErrorKind::InvalidNumber, Span::new(0, 2))]
+    #[case::binary_digit_out_of_radix("0b102", LexErrorKind::InvalidNumber, Span::new(0, 5))]
+    #[case::hex_fraction("0x1.5", LexErrorKind::InvalidNumber, Span::new(0, 5))]
+    #[case::integer_overflow("9223372036854775808", LexErrorKind::NumberOutOfRange, Span::new(0, 19))]
+    #[case::hex_overflow("0xffff_ffff_ffff_ffff", LexErrorKind::NumberOutOfRange, Span::new(0, 21))]
+    #[case::decimal_overflow("1e999", LexErrorKind::NumberOutOfRange, Span::new(0, 5))]
+    fn invalid_number_literals(#[case] expression: &str, #[case] kind: LexErrorKind, #[case] span: Span) {
+        assert_eq!(string_to_tokens(expression), Err(EvalError::Lex { kind, span }));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[rstest]
+    #[case::fraction("19.99d", "19.99")]
+    #[case::keeps_trailing_zeros("2.50d", "2.50")]
+    #[case::integer("5d", "5")]
+    #[case::leading_dot(".5d", "0.5")]
+    #[case::separators("1_000.000_1d", "1000.0001")]
+    #[case::exponent("1.5e3d", "1500")]
+    #[case::many_digits("0.1234567890123456789012345678d", "0.1234567890123456789012345678")]
+    fn money_literals(#[case] expression: &str, #[case] expected: &str) {
+        let tokens = string_to_tokens(expression).unwrap();
+        let expected = Literal::Money(rust_decimal::Decimal::from_str_exact(expected).unwrap());
+
+        assert_eq!(tokens, Vec::from([SpannedToken::new(Token::Literal(expected), Span::new(0, expression.len()))]));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[rstest]
+    #[case::letters_after_suffix("1.5dx", LexErrorKind::InvalidNumber, Span::new(0, 5))]
+    #[case::double_suffix("1dd", LexErrorKind::InvalidNumber, Span::new(0, 3))]
+    #[case::too_many_digits("0.12345678901234567890123456789d", LexErrorKind::NumberOutOfRange, Span::new(0, 32))]
+    #[case::too_large("1e40d", LexErrorKind::NumberOutOfRange, Span::new(0, 5))]
+    fn invalid_money_literals(#[case] expression: &str, #[case] kind: LexErrorKind, #[case] span: Span) {
+        assert_eq!(string_to_tokens(expression), Err(EvalError::Lex { kind, span }));
+    }
+
+    #[cfg(not(feature = "decimal"))]
+    #[test]
+    fn money_literals_require_the_decimal_feature() {
+        assert_eq!(string_to_tokens("19.99d"), Err(EvalError::Lex { kind: LexErrorKind::InvalidNumber, span: Span::new(0, 6) }));
+    }
+
+    #[test]
+    fn bitwise_operators_next_to_logical_operators() {
+        let tokens: Vec<Token> = string_to_tokens("1&2&&3|4||5<<6<=7>>>8").unwrap().into_iter().map(|spanned| spanned.token).collect();
+        let integer = |value| Token::Literal(Literal::Integer(value));
+
+        assert_eq!(tokens, Vec::from([
+            integer(1), BIT_AND_OPERATOR, integer(2), AND_OPERATOR, integer(3), BIT_OR_OPERATOR, integer(4), OR_OPERATOR,
+            integer(5), SHIFT_LEFT_OPERATOR, integer(6), LESS_OR_EQUAL_OPERATOR, integer(7), SHIFT_RIGHT_OPERATOR, GREATER_OPERATOR, integer(8),
+        ]));
+    }
+
+    #[test]
+    fn conditional_tokens() {
+        let tokens: Vec<Token> = string_to_tokens("a?b:c??d").unwrap().into_iter().map(|spanned| spanned.token).collect();
+        let variable = |name| Token::Variable(VariablePath::new(name));
+
+        assert_eq!(tokens, Vec::from([variable("a"), Token::QuestionMark, variable("b"), Token::Colon, variable("c"), COALESCE_OPERATOR, variable("d")]));
+
+        let tokens: Vec<Token> = string_to_tokens("if a then b else c").unwrap().into_iter().map(|spanned| spanned.token).collect();
+        assert_eq!(tokens, Vec::from([Token::Keyword(Keyword::If), variable("a"), Token::Keyword(Keyword::Then), variable("b"), Token::Keyword(Keyword::Else), variable("c")]));
+    }
+
+    #[rstest]
+    #[case::is_null("a is null", IS_NULL_OPERATOR, Span::new(2, 9))]
+    #[case::is_not_null("a is  not\tnull", IS_NOT_NULL_OPERATOR, Span::new(2, 14))]
+    fn null_tests(#[case] expression: &str, #[case] expected: Token, #[case] span: Span) {
+        let tokens = string_to_tokens(expression).unwrap();
+
+        assert_eq!(tokens.last(), Some(&SpannedToken::new(expected, span)));
+    }
+
+    #[rstest]
+    #[case::alone("a is", Span::new(2, 4))]
+    #[case::not_alone("a is not", Span::new(2, 8))]
+    #[case::other_keyword("a is true", Span::new(2, 9))]
+    #[case::not_other_keyword("a is not b", Span::new(2, 10))]
+    fn invalid_null_tests(#[case] expression: &str, #[case] span: Span) {
+        assert_eq!(string_to_tokens(expression), Err(EvalError::Lex { kind: LexErrorKind::InvalidNullTest, span }));
+    }
+
+    #[test]
+    fn number_keeps_following_operator() {
+        let tokens: Vec<Token> = string_to_tokens("3+.5e1").unwrap().into_iter().map(|spanned| spanned.token).collect();
+
+        assert_eq!(tokens, Vec::from([Token::Literal(Literal::Integer(3)), PLUS_OPERATOR, Token::Literal(Literal::Decimal(5.0))]));
+    }
+
+    #[test]
+    fn invalid_number_reports_span() {
+        let result = string_to_tokens("a == 1.2.3");
+        assert_eq!(result, Err(EvalError::Lex { kind: LexErrorKind::InvalidNumber, span: Span::new(5, 10) }));
+    }
+
+    #[test]
+    fn tokens_record_their_position() {
+        let spans: Vec<Span> = string_to_tokens("ab <= 'x' − 12").unwrap().into_iter().map(|spanned| spanned.span).collect();
+        assert_eq!(spans, Vec::from([Span::new(0, 2), Span::new(3, 5), Span::new(6, 9), Span::new(10, 13), Span::new(14, 16)]));
+    }
+
+    #[test]
+    fn operators_without_whitespace() {
+        let result: Vec<Token> = string_to_tokens("a<=3==b").unwrap().into_iter().map(|spanned| spanned.token).collect();
+        let expected = Vec::from([Token::Variable(VariablePath::from("a")), LESS_OR_EQUAL_OPERATOR, Token::Literal(Literal::Integer(3)), EQUAL_OPERATOR, Token::Variable(VariablePath::from("b"))]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case::dollar("3 $ 4", '$', 2)]
+    #[case::hash("a # b", '#', 2)]
+    #[case::at("a @ b", '@', 2)]
+    #[case::lone_equal("a = b", '=', 2)]
+    #[case::directly_after_number("3$", '$', 1)]
+    #[case::non_ascii("a € b", '€', 2)]
+    fn strict_mode_rejects_unknown_characters(#[case] expression: &str, #[case] character: char, #[case] position: usize) {
+        let span = Span::new(position, position + character.len_utf8());
+        assert_eq!(string_to_tokens(expression), Err(EvalError::Lex { kind: LexErrorKind::UnexpectedCharacter(character), span }));
+    }
+
+    #[test]
+    fn strict_mode_over_ascii_range() {
+        let accepted = |character: char| character.is_ascii_alphanumeric()
+            || character.is_whitespace()
+            || "^+-*/()!<>,_?:%~|&".contains(character);
+
+        for character in (0u8..128).map(char::from) {
+            let expression = format!("1 {} 2", character);
+            let result = string_to_tokens(&expression);
+
+            if character == '\'' || character == '"' {
+                let kind = LexErrorKind::UnterminatedString;
+                assert_eq!(result, Err(EvalError::Lex { kind, span: Span::new(2, 5) }), "{:?} should start a string", character);
+            } else if accepted(character) {
+                assert!(result.is_ok(), "{:?} should be accepted", character);
+            } else {
+                let kind = LexErrorKind::UnexpectedCharacter(character);
+                assert_eq!(result, Err(EvalError::Lex { kind, span: Span::new(2, 3) }), "{:?} should be rejected", character);
+            }
+        }
+    }
+
+    #[test]
+    fn lenient_mode_over_ascii_range() {
+        // a lone quote starts an unterminated string literal, which is an error in both modes
+        for character in (0u8..128).map(char::from).filter(|character| *character != '\'' && *character != '"') {
+            let expression = format!("1 {} 2", character);
+            assert!(string_to_tokens_with_mode(&expression, LexMode::Lenient).is_ok(), "{:?} should be skipped", character);
+        }
+
+        let result: Vec<Token> = string_to_tokens_with_mode("3 $ # @ 4", LexMode::Lenient).unwrap().into_iter().map(|spanned| spanned.token).collect();
+        assert_eq!(result, Vec::from([Token::Literal(Literal::Integer(3)), Token::Literal(Literal::Integer(4))]));
+    }
+
+}